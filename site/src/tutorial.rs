@@ -199,7 +199,7 @@ fn TutorialBasic() -> impl IntoView {
         <p><Prim prim=Trace/>" prints the top item on the stack without popping it."</p>
         <p>"It also attaches line and column numbers."</p>
         <p>"This is useful for debugging by inpecting the stack."</p>
-        <Editor example="+1 ~ ×4 ~ ×. -3 5"/>
+        <Editor example="+1 ⸮ ×4 ⸮ ×. -3 5"/>
     }
 }
 
@@ -715,6 +715,18 @@ Code ← $ &p \"Loading module\"
        $ rand
 &fwa \"test.ua\" Code
 ⍥(&i \"test.ua\")3"/>
+
+        <h2 id="namespaced-imports">"Namespaced Imports"</h2>
+        <p>"Extracting every function with "<Prim prim=Use/>" gets tedious. Instead, you can give an imported file a name with "<code>"~"</code>". Its bindings can then be referred to with that name as a prefix."</p>
+        <Editor example=r#"Ex ~ "example.ua"
+Ex~Increment Ex~Square Ex~Double 5"#/>
+        <p>"You can also bring specific bindings into scope directly by listing them after a second "<code>"~"</code>"."</p>
+        <Editor example=r#"~ "example.ua" ~ Square Double
+Square Double 5"#/>
+        <p>"Bindings whose name starts with "<code>"~"</code>" are private. They can be used in the file they are defined in, but they are not available to files that import it."</p>
+        <Editor example=r#"~Helper ← ×2
+Public ← +1 Helper
+Public 5"#/>
     }
 }
 
@@ -744,5 +756,6 @@ fn TutorialTesting() -> impl IntoView {
         <p><code>"uiua watch"</code>" will run all code, including tests."</p>
         <p><code>"uiua run"</code>" will only run non-test code."</p>
         <p><code>"uiua test"</code>" will only run test code, but also any non-test bindings and any non-test code which makes imports."</p>
+        <p><code>"uiua test"</code>" runs each test scope as a separate test, so a failing test does not stop the ones after it. A comment on the first line of a test scope is used as its name. "<code>"--filter"</code>" only runs tests whose names contain some text, and "<code>"--junit"</code>" writes a JUnit XML report for CI systems."</p>
     }
 }
@@ -121,6 +121,9 @@ impl Value {
             (Value::Char(a), Value::Char(b)) => a.join_impl(b, ctx)?.into(),
             (Value::Byte(a), Value::Num(b)) => a.convert().join_impl(b, ctx)?.into(),
             (Value::Num(a), Value::Byte(b)) => a.join_impl(b.convert(), ctx)?.into(),
+            (a @ Value::Bits(_), b) | (a, b @ Value::Bits(_)) => {
+                a.unpack().join_impl(b.unpack(), ctx)?
+            }
             (a, b) => a.coerce_to_functions(
                 b,
                 ctx,
@@ -141,6 +144,9 @@ impl Value {
             (Value::Char(a), Value::Char(b)) => a.append(b, ctx, action)?.into(),
             (Value::Byte(a), Value::Num(b)) => a.convert().append(b, ctx, action)?.into(),
             (Value::Num(a), Value::Byte(b)) => a.append(b.convert(), ctx, action)?.into(),
+            (a @ Value::Bits(_), b) | (a, b @ Value::Bits(_)) => {
+                a.unpack().append(b.unpack(), ctx, action)?
+            }
             (a, b) => a.coerce_to_functions(
                 b,
                 ctx,
@@ -282,6 +288,9 @@ impl Value {
             (Value::Func(a), Value::Func(b)) => a.couple_impl(b, ctx)?.into(),
             (Value::Num(a), Value::Byte(b)) => a.couple_impl(b.convert(), ctx)?.into(),
             (Value::Byte(a), Value::Num(b)) => a.convert().couple_impl(b, ctx)?.into(),
+            (a @ Value::Bits(_), b) | (a, b @ Value::Bits(_)) => {
+                a.unpack().couple_impl(b.unpack(), ctx)?
+            }
             (a, b) => a.coerce_to_functions(
                 b,
                 ctx,
@@ -296,6 +305,7 @@ impl Value {
             Value::Byte(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Char(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
             Value::Func(a) => a.uncouple(env).map(|(a, b)| (a.into(), b.into())),
+            Value::Bits(bits) => Value::Byte(bits.into_bytes()).uncouple(env),
         }
     }
 }
@@ -418,12 +428,14 @@ impl<T: ArrayValue> Array<T> {
 
 impl Value {
     pub fn reshape(&mut self, shape: &Self, env: &Uiua) -> UiuaResult {
+        self.unpack_mut();
         if let Ok(n) = shape.as_nat(env, "") {
             match self {
                 Value::Num(a) => a.reshape_scalar(n),
                 Value::Byte(a) => a.reshape_scalar(n),
                 Value::Char(a) => a.reshape_scalar(n),
                 Value::Func(a) => a.reshape_scalar(n),
+                Value::Bits(_) => unreachable!(),
             }
         } else {
             let target_shape = shape.as_integers(
@@ -436,6 +448,7 @@ impl Value {
                 Value::Byte(a) => a.reshape(&target_shape, env),
                 Value::Char(a) => a.reshape(&target_shape, env),
                 Value::Func(a) => a.reshape(&target_shape, env),
+                Value::Bits(_) => unreachable!(),
             }?
         }
         Ok(())
@@ -556,6 +569,18 @@ impl<T: ArrayValue> Array<T> {
 
 impl Value {
     pub fn keep(&self, kept: Self, env: &Uiua) -> UiuaResult<Self> {
+        let kept = kept.unpack();
+        if let Value::Bits(bits) = self {
+            if bits.rank() == 1 && kept.rank() > 0 && bits.flat_len() == kept.row_count() {
+                return Ok(match kept {
+                    Value::Num(a) => bits.keep(&a).into(),
+                    Value::Byte(a) => bits.keep(&a).into(),
+                    Value::Char(a) => bits.keep(&a).into(),
+                    Value::Func(a) => bits.keep(&a).into(),
+                    Value::Bits(_) => unreachable!(),
+                });
+            }
+        }
         let counts = self.as_naturals(
             env,
             "Keep amount must be a natural number \
@@ -567,6 +592,7 @@ impl Value {
                 Value::Byte(a) => a.scalar_keep(counts[0]).into(),
                 Value::Char(a) => a.scalar_keep(counts[0]).into(),
                 Value::Func(a) => a.scalar_keep(counts[0]).into(),
+                Value::Bits(_) => unreachable!(),
             }
         } else {
             match kept {
@@ -574,6 +600,7 @@ impl Value {
                 Value::Byte(a) => a.list_keep(&counts, env)?.into(),
                 Value::Char(a) => a.list_keep(&counts, env)?.into(),
                 Value::Func(a) => a.list_keep(&counts, env)?.into(),
+                Value::Bits(_) => unreachable!(),
             }
         })
     }
@@ -762,7 +789,7 @@ impl<T: ArrayValue> Array<T> {
 
 impl Value {
     pub(crate) fn into_shaped_indices(self, env: &Uiua) -> UiuaResult<(Shape, Vec<isize>)> {
-        Ok(match self {
+        Ok(match self.unpack() {
             Value::Num(arr) => {
                 let mut index_data = Vec::with_capacity(arr.flat_len());
                 for n in arr.data {
@@ -792,7 +819,7 @@ impl Value {
     }
     pub fn pick(self, from: Self, env: &Uiua) -> UiuaResult<Self> {
         let (index_shape, index_data) = self.into_shaped_indices(env)?;
-        Ok(match from {
+        Ok(match from.unpack() {
             Value::Num(a) => Value::Num(a.pick_shaped(&index_shape, &index_data, env)?),
             Value::Byte(a) => op_bytes_retry_fill(
                 a,
@@ -801,6 +828,7 @@ impl Value {
             )?,
             Value::Char(a) => Value::Char(a.pick_shaped(&index_shape, &index_data, env)?),
             Value::Func(a) => Value::Func(a.pick_shaped(&index_shape, &index_data, env)?),
+            Value::Bits(_) => unreachable!(),
         })
     }
     pub fn unpick(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -927,6 +955,7 @@ impl Value {
             )?,
             Value::Char(a) => Value::Char(a.take(&index, env)?),
             Value::Func(a) => Value::Func(a.take(&index, env)?),
+            Value::Bits(bits) => self.take(Value::Byte(bits.into_bytes()), env)?,
         })
     }
     pub fn drop(self, from: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -939,6 +968,7 @@ impl Value {
             Value::Byte(a) => Value::Byte(a.drop(&index, env)?),
             Value::Char(a) => Value::Char(a.drop(&index, env)?),
             Value::Func(a) => Value::Func(a.drop(&index, env)?),
+            Value::Bits(bits) => self.drop(Value::Byte(bits.into_bytes()), env)?,
         })
     }
     pub(crate) fn untake(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -1260,11 +1290,13 @@ impl<T: ArrayValue> Array<T> {
 impl Value {
     pub fn rotate(&self, mut rotated: Self, env: &Uiua) -> UiuaResult<Self> {
         let by = self.as_indices(env, "Rotation amount must be a list of integers")?;
+        rotated.unpack_mut();
         match &mut rotated {
             Value::Num(a) => a.rotate(&by, env)?,
             Value::Byte(a) => a.rotate(&by, env)?,
             Value::Char(a) => a.rotate(&by, env)?,
             Value::Func(a) => a.rotate(&by, env)?,
+            Value::Bits(_) => unreachable!(),
         }
         Ok(rotated)
     }
@@ -1348,6 +1380,7 @@ impl Value {
             )?,
             Value::Char(a) => a.select_impl(indices_shape, &indices, env)?.into(),
             Value::Func(a) => a.select_impl(indices_shape, &indices, env)?.into(),
+            Value::Bits(bits) => self.select(&Value::Byte(bits.to_bytes()), env)?,
         })
     }
     pub fn unselect(self, index: Self, into: Self, env: &Uiua) -> UiuaResult<Self> {
@@ -1516,11 +1549,44 @@ impl Value {
             Value::Byte(a) => a.windows(&size_spec, env)?.into(),
             Value::Char(a) => a.windows(&size_spec, env)?.into(),
             Value::Func(a) => a.windows(&size_spec, env)?.into(),
+            Value::Bits(bits) => self.windows(&Value::Byte(bits.to_bytes()), env)?,
         })
     }
+    /// Iterate over the windows of a scalar size along the first axis without copying them
+    pub fn row_windows(&self, size: usize) -> Box<dyn Iterator<Item = Self> + '_> {
+        match self {
+            Value::Num(a) => Box::new(a.row_windows(size).map(Value::from)),
+            Value::Byte(a) => Box::new(a.row_windows(size).map(Value::from)),
+            Value::Char(a) => Box::new(a.row_windows(size).map(Value::from)),
+            Value::Func(a) => Box::new(a.row_windows(size).map(Value::from)),
+            Value::Bits(bits) => {
+                let bytes = bits.to_bytes();
+                let windows: Vec<Self> = bytes.row_windows(size).map(Value::from).collect();
+                Box::new(windows.into_iter())
+            }
+        }
+    }
 }
 
 impl<T: ArrayValue> Array<T> {
+    /// Iterate over the windows of a scalar size along the first axis
+    ///
+    /// Each window shares its data with the array, so this is the same as
+    /// the rows of the result of `windows` without materializing them all.
+    pub fn row_windows(&self, size: usize) -> impl Iterator<Item = Self> + '_ {
+        let row_len = self.row_len();
+        let mut shape = self.shape.clone();
+        if let Some(first) = shape.first_mut() {
+            *first = size;
+        }
+        let count = (self.row_count() + 1).saturating_sub(size);
+        (0..count).map(move |i| {
+            Array::new(
+                shape.clone(),
+                self.data.slice(i * row_len..(i + size) * row_len),
+            )
+        })
+    }
     pub fn windows(&self, size_spec: &[usize], env: &Uiua) -> UiuaResult<Self> {
         if size_spec.len() > self.shape.len() {
             return Err(env.error(format!(
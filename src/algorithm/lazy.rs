@@ -0,0 +1,178 @@
+//! Kernels that skip materializing some arrays
+//!
+//! There is no lazy array value. Instead, the compiler recognizes only these
+//! adjacent pairs, and runs them without allocating the array:
+//!
+//! - `⇡` directly followed by `⧻`, `△`, `⊢`, `⊢⇌`, `⊏`, `/` of `+`, `×`, `↥`, or `↧`,
+//!   or `⊞` of a primitive
+//! - `↯` of a scalar directly followed by `⧻`, `△`, `⊢`, `⊢⇌`, or `/` of `+`, `↥`, or `↧`
+//!
+//! A range or reshape that is bound to a name, moved with a stack primitive like
+//! `.` or `∶`, or used in any other way is allocated as usual.
+
+use crate::{
+    algorithm::loops::{table_nums, TableLeft},
+    array::Shape,
+    function::Instr,
+    primitive::Primitive,
+    run::ArrayArg,
+    value::Value,
+    Uiua, UiuaResult,
+};
+
+/// The list `⇡len`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyRange {
+    pub len: usize,
+}
+
+impl LazyRange {
+    /// Get the range of a scalar natural number
+    pub fn new(val: &Value) -> Option<Self> {
+        let len = match val {
+            Value::Num(n) if n.rank() == 0 && n.data[0].fract() == 0.0 && n.data[0] >= 0.0 => {
+                n.data[0] as usize
+            }
+            Value::Byte(n) if n.rank() == 0 => n.data[0] as usize,
+            _ => return None,
+        };
+        Some(LazyRange { len })
+    }
+    /// The sum of the range, if adding its elements in order would give exactly the same result
+    fn sum(self) -> Option<f64> {
+        let len = self.len as u128;
+        let sum = len * len.saturating_sub(1) / 2;
+        (sum <= 1 << 53).then_some(sum as f64)
+    }
+}
+
+impl TableLeft<f64> for LazyRange {
+    fn shape(&self) -> Shape {
+        Shape::from([self.len].as_slice())
+    }
+    fn elements(self) -> impl Iterator<Item = f64> {
+        (0..self.len).map(|i| i as f64)
+    }
+}
+
+/// Run the instructions after a `⇡` without materializing the range
+///
+/// Returns `false` without touching the stack if the range must be materialized.
+pub(crate) fn consume_range(consumer: &[Instr], env: &mut Uiua) -> UiuaResult<bool> {
+    use Primitive::*;
+    let Some(range) = env.stack.last().and_then(LazyRange::new) else {
+        return Ok(false);
+    };
+    let len = range.len;
+    let res: Value = match consumer {
+        [Instr::Prim(Len, _)] => (len as f64).into(),
+        [Instr::Prim(Shape, _)] => [len].into_iter().collect(),
+        [Instr::Prim(First, _)] if len > 0 => 0.0.into(),
+        [Instr::Prim(Last, _)] if len > 0 => ((len - 1) as f64).into(),
+        [Instr::Prim(Select, _)] => {
+            let Some(xs) = env.stack.iter().rev().nth(1) else {
+                return Ok(false);
+            };
+            if xs.rank() == 0 || len > xs.row_count() {
+                return Ok(false);
+            }
+            // The first window is the first `len` rows
+            let rows = xs.row_windows(len).next().unwrap();
+            env.pop(ArrayArg(1))?;
+            env.pop(ArrayArg(2))?;
+            env.push(rows);
+            return Ok(true);
+        }
+        [Instr::Push(f), Instr::Prim(Reduce, _)] => {
+            let Some((prim, _)) = f.as_flipped_primitive() else {
+                return Ok(false);
+            };
+            match prim {
+                Add => match range.sum() {
+                    Some(sum) => sum.into(),
+                    None => return Ok(false),
+                },
+                Mul if len == 0 => 1.0.into(),
+                Mul => 0.0.into(),
+                Max if len == 0 => f64::NEG_INFINITY.into(),
+                Max => ((len - 1) as f64).into(),
+                Min if len == 0 => f64::INFINITY.into(),
+                Min => 0.0.into(),
+                _ => return Ok(false),
+            }
+        }
+        [Instr::Push(f), Instr::Prim(Table, _)] => {
+            let Some((prim, flipped)) = f.as_flipped_primitive() else {
+                return Ok(false);
+            };
+            let ys = match env.stack.iter().rev().nth(1) {
+                Some(Value::Num(ys)) => ys.clone(),
+                Some(Value::Byte(ys)) => ys.convert_ref(),
+                _ => return Ok(false),
+            };
+            let range_arg = env.pop(ArrayArg(1))?;
+            let ys_arg = env.pop(ArrayArg(2))?;
+            if table_nums(prim, flipped, range, ys, env).is_err() {
+                env.push(ys_arg);
+                env.push(range_arg);
+                return Ok(false);
+            }
+            return Ok(true);
+        }
+        _ => return Ok(false),
+    };
+    env.pop(ArrayArg(1))?;
+    env.push(res);
+    Ok(true)
+}
+
+/// Run the instructions after a `↯` of a scalar number without materializing the array
+///
+/// Returns `false` without touching the stack if the array must be materialized.
+pub(crate) fn consume_reshape(consumer: &[Instr], env: &mut Uiua) -> UiuaResult<bool> {
+    use Primitive::*;
+    let mut args = env.stack.iter().rev();
+    let (Some(shape), Some(fill)) = (args.next(), args.next()) else {
+        return Ok(false);
+    };
+    let shape: Vec<usize> = match shape {
+        Value::Num(n) if n.rank() <= 1 && n.data.iter().all(|&d| d.fract() == 0.0 && d >= 0.0) => {
+            n.data.iter().map(|&d| d as usize).collect()
+        }
+        Value::Byte(n) if n.rank() <= 1 => n.data.iter().map(|&d| d as usize).collect(),
+        _ => return Ok(false),
+    };
+    let n = match fill {
+        Value::Num(n) if n.rank() == 0 => n.data[0],
+        Value::Byte(n) if n.rank() == 0 => n.data[0] as f64,
+        _ => return Ok(false),
+    };
+    let list_len = match shape.as_slice() {
+        [] => return Ok(false),
+        &[len] => Some(len),
+        _ => None,
+    };
+    let res: Value = match (consumer, list_len) {
+        ([Instr::Prim(Len, _)], _) => (shape[0] as f64).into(),
+        ([Instr::Prim(Shape, _)], _) => shape.into_iter().collect(),
+        ([Instr::Prim(First | Last, _)], Some(len)) if len > 0 => fill.clone(),
+        ([Instr::Push(f), Instr::Prim(Reduce, _)], Some(len)) if len > 0 => {
+            let Some((prim, _)) = f.as_flipped_primitive() else {
+                return Ok(false);
+            };
+            match prim {
+                // The sum must be what adding the elements in order would give
+                Add if n.fract() == 0.0 && (n * len as f64).abs() <= (1u64 << 53) as f64 => {
+                    (n * len as f64).into()
+                }
+                Max | Min => fill.clone(),
+                _ => return Ok(false),
+            }
+        }
+        _ => return Ok(false),
+    };
+    env.pop(ArrayArg(1))?;
+    env.pop(ArrayArg(2))?;
+    env.push(res);
+    Ok(true)
+}
@@ -1,13 +1,22 @@
 //! Algorithms for looping modifiers
 
-use std::ops::{Add, Div, Mul, Sub};
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    ops::{Add, Div, Mul, Sub},
+};
 
 use tinyvec::tiny_vec;
 
 use crate::{
-    algorithm::pervade::bin_pervade_generic,
+    algorithm::{
+        par::{par_ranges, LOOP_CHUNK, PERVADE_CHUNK},
+        pervade::bin_pervade_generic,
+    },
     array::{Array, ArrayValue, FormatShape, Shape},
     cowslice::cowslice,
+    function::{Instr, Signature},
+    optimize::Fused,
     primitive::Primitive,
     run::{ArrayArg, FunctionArg},
     value::Value,
@@ -21,29 +30,52 @@ fn flip<A, B, C>(f: impl Fn(A, B) -> C) -> impl Fn(B, A) -> C {
 pub fn reduce(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
     let f = env.pop(FunctionArg(1))?;
-    let xs = env.pop(ArrayArg(1))?;
+    let xs = match (f.as_flipped_primitive(), env.pop_packed(ArrayArg(1))?) {
+        // Counting the set bits of a mask
+        (Some((Primitive::Add, _)), Value::Bits(bits)) if bits.rank() == 1 => {
+            env.push(bits.count_ones() as f64);
+            return Ok(());
+        }
+        (_, xs) => xs.unpack(),
+    };
 
     match (f.as_flipped_primitive(), xs) {
         (Some((prim, flipped)), Value::Num(nums)) => env.push(match prim {
-            Primitive::Add => fast_reduce(nums, 0.0, Add::add),
-            Primitive::Sub if flipped => fast_reduce(nums, 0.0, Sub::sub),
-            Primitive::Sub => fast_reduce(nums, 0.0, flip(Sub::sub)),
-            Primitive::Mul => fast_reduce(nums, 1.0, Mul::mul),
-            Primitive::Div if flipped => fast_reduce(nums, 1.0, Div::div),
-            Primitive::Div => fast_reduce(nums, 1.0, flip(Div::div)),
-            Primitive::Max => fast_reduce(nums, f64::NEG_INFINITY, f64::max),
-            Primitive::Min => fast_reduce(nums, f64::INFINITY, f64::min),
+            Primitive::Add if nums.rank() == 1 => sum_nums(env, &nums.data).into(),
+            Primitive::Add => fast_reduce(nums, env, 0.0, Add::add),
+            Primitive::Sub if flipped => fast_reduce(nums, env, 0.0, Sub::sub),
+            Primitive::Sub => fast_reduce(nums, env, 0.0, flip(Sub::sub)),
+            Primitive::Mul => fast_reduce(nums, env, 1.0, Mul::mul),
+            Primitive::Div if flipped => fast_reduce(nums, env, 1.0, Div::div),
+            Primitive::Div => fast_reduce(nums, env, 1.0, flip(Div::div)),
+            Primitive::Max if nums.rank() == 1 => {
+                par_reduce(env, &nums.data, f64::NEG_INFINITY, f64::max).into()
+            }
+            Primitive::Min if nums.rank() == 1 => {
+                par_reduce(env, &nums.data, f64::INFINITY, f64::min).into()
+            }
+            Primitive::Max => fast_reduce(nums, env, f64::NEG_INFINITY, f64::max),
+            Primitive::Min => fast_reduce(nums, env, f64::INFINITY, f64::min),
             _ => return generic_fold1(f, Value::Num(nums), None, env),
         }),
         (Some((prim, flipped)), Value::Byte(bytes)) => env.push(match prim {
-            Primitive::Add => fast_reduce(bytes, 0.0, |a, b| a + f64::from(b)),
-            Primitive::Sub if flipped => fast_reduce(bytes, 0.0, |a, b| a - f64::from(b)),
-            Primitive::Sub => fast_reduce(bytes, 0.0, |a, b| f64::from(b) - a),
-            Primitive::Mul => fast_reduce(bytes, 1.0, |a, b| a * f64::from(b)),
-            Primitive::Div if flipped => fast_reduce(bytes, 1.0, |a, b| a / f64::from(b)),
-            Primitive::Div => fast_reduce(bytes, 1.0, |a, b| f64::from(b) / a),
-            Primitive::Max => fast_reduce(bytes, f64::NEG_INFINITY, |a, b| a.max(f64::from(b))),
-            Primitive::Min => fast_reduce(bytes, f64::INFINITY, |a, b| a.min(f64::from(b))),
+            Primitive::Add if bytes.rank() == 1 => sum_bytes(env, &bytes.data).into(),
+            Primitive::Max if bytes.rank() == 1 && bytes.row_count() > 0 => {
+                f64::from(par_reduce(env, &bytes.data, 0, u8::max)).into()
+            }
+            Primitive::Min if bytes.rank() == 1 && bytes.row_count() > 0 => {
+                f64::from(par_reduce(env, &bytes.data, 0, u8::min)).into()
+            }
+            Primitive::Add => fast_reduce(bytes, env, 0.0, |a, b| a + f64::from(b)),
+            Primitive::Sub if flipped => fast_reduce(bytes, env, 0.0, |a, b| a - f64::from(b)),
+            Primitive::Sub => fast_reduce(bytes, env, 0.0, |a, b| f64::from(b) - a),
+            Primitive::Mul => fast_reduce(bytes, env, 1.0, |a, b| a * f64::from(b)),
+            Primitive::Div if flipped => fast_reduce(bytes, env, 1.0, |a, b| a / f64::from(b)),
+            Primitive::Div => fast_reduce(bytes, env, 1.0, |a, b| f64::from(b) / a),
+            Primitive::Max => {
+                fast_reduce(bytes, env, f64::NEG_INFINITY, |a, b| a.max(f64::from(b)))
+            }
+            Primitive::Min => fast_reduce(bytes, env, f64::INFINITY, |a, b| a.min(f64::from(b))),
             _ => return generic_fold1(f, Value::Byte(bytes), None, env),
         }),
         (_, xs) => generic_fold1(f, xs, None, env)?,
@@ -51,10 +83,89 @@ pub fn reduce(env: &mut Uiua) -> UiuaResult {
     Ok(())
 }
 
+const LANES: usize = 8;
+
+/// Reduce a list in independent lanes, which lets the loop be vectorized
+///
+/// This is only correct for functions whose result does not depend on the order
+/// of the elements or on how many times each one is seen, like `max` and `min`.
+/// Float addition and multiplication round differently in a different order,
+/// so they are always reduced one element after another.
+fn lane_reduce<T: Copy>(data: &[T], identity: T, f: impl Fn(T, T) -> T) -> T {
+    let Some(&first) = data.first() else {
+        return identity;
+    };
+    let mut lanes = [first; LANES];
+    let mut chunks = data.chunks_exact(LANES);
+    for chunk in chunks.by_ref() {
+        for (lane, &x) in lanes.iter_mut().zip(chunk) {
+            *lane = f(*lane, x);
+        }
+    }
+    let acc = chunks.remainder().iter().fold(first, |acc, &x| f(acc, x));
+    lanes.into_iter().fold(acc, f)
+}
+
+/// Reduce a list across threads
+///
+/// Like [`lane_reduce`], this is only correct for functions that do not depend on order.
+fn par_reduce<T: Copy + Send + Sync>(
+    env: &Uiua,
+    data: &[T],
+    identity: T,
+    f: impl Fn(T, T) -> T + Sync,
+) -> T {
+    let partials = par_ranges(env, data.len(), PERVADE_CHUNK, |range| {
+        Ok::<_, Infallible>(vec![lane_reduce(&data[range], identity, &f)])
+    })
+    .unwrap_or_else(|e| match e {});
+    lane_reduce(&partials, identity, &f)
+}
+
+/// Sum bytes as integers, which is exact in any order
+pub(crate) fn sum_bytes(env: &Uiua, data: &[u8]) -> f64 {
+    let partials = par_ranges(env, data.len(), PERVADE_CHUNK, |range| {
+        Ok::<_, Infallible>(vec![data[range].iter().map(|&b| u64::from(b)).sum::<u64>()])
+    })
+    .unwrap_or_else(|e| match e {});
+    partials.into_iter().sum::<u64>() as f64
+}
+
+/// Sum a list of numbers
+///
+/// Sums of integers whose magnitudes add up to less than 2⁵² are exact in any order,
+/// so they are split across threads. Other lists are summed one element after another.
+fn sum_nums(env: &Uiua, data: &[f64]) -> f64 {
+    let partials = par_ranges(env, data.len(), PERVADE_CHUNK, |range| {
+        let chunk = &data[range];
+        let integers = chunk.iter().all(|x| x.fract() == 0.0);
+        let magnitude: f64 = chunk.iter().map(|x| x.abs()).sum();
+        let sum = chunk.iter().copied().reduce(Add::add).unwrap_or(0.0);
+        Ok::<_, Infallible>(vec![(integers, magnitude, sum)])
+    })
+    .unwrap_or_else(|e| match e {});
+    let exact = partials.iter().all(|&(integers, ..)| integers)
+        && partials
+            .iter()
+            .map(|&(_, magnitude, _)| magnitude)
+            .sum::<f64>()
+            < (1u64 << 52) as f64;
+    if exact || partials.len() <= 1 {
+        partials
+            .into_iter()
+            .map(|(.., sum)| sum)
+            .reduce(Add::add)
+            .unwrap_or(0.0)
+    } else {
+        data.iter().copied().reduce(Add::add).unwrap_or(0.0)
+    }
+}
+
 pub fn fast_reduce<T: ArrayValue + Into<R>, R: ArrayValue>(
     mut arr: Array<T>,
+    env: &Uiua,
     identity: R,
-    f: impl Fn(R, T) -> R,
+    f: impl Fn(R, T) -> R + Sync,
 ) -> Array<R> {
     match arr.shape.len() {
         0 => Array::new(
@@ -80,17 +191,21 @@ pub fn fast_reduce<T: ArrayValue + Into<R>, R: ArrayValue>(
                 let data = cowslice![identity; row_len];
                 return Array::new(arr.shape, data);
             }
-            let mut new_data: Vec<R> = arr.data[..row_len]
-                .iter()
-                .cloned()
-                .map(Into::into)
-                .collect();
-            for i in 1..row_count {
-                let start = i * row_len;
-                for j in 0..row_len {
-                    new_data[j] = f(new_data[j].clone(), arr.data[start + j].clone());
+            // Columns are reduced independently, so they can be split across threads
+            // without changing the order in which each one is folded
+            let min_chunk = (PERVADE_CHUNK / row_count).max(1);
+            let data = &arr.data;
+            let new_data = par_ranges(env, row_len, min_chunk, |cols| {
+                let mut acc: Vec<R> = data[cols.clone()].iter().cloned().map(Into::into).collect();
+                for i in 1..row_count {
+                    let row = &data[i * row_len..][cols.clone()];
+                    for (acc, x) in acc.iter_mut().zip(row) {
+                        *acc = f(acc.clone(), x.clone());
+                    }
                 }
-            }
+                Ok::<_, Infallible>(acc)
+            })
+            .unwrap_or_else(|e| match e {});
             arr.shape.remove(0);
             Array::new(arr.shape, new_data)
         }
@@ -352,21 +467,54 @@ pub fn each(env: &mut Uiua) -> UiuaResult {
     }
 }
 
+/// Check if calling a function on `len` parts of an array can be split across threads
+fn can_par_loop(f: &Value, xs: &Value, len: usize, env: &Uiua) -> bool {
+    env.max_threads() > 1
+        && len >= 2 * LOOP_CHUNK
+        && !matches!(xs, Value::Func(_))
+        && f.as_function().is_some_and(|f| f.is_pure())
+}
+
+/// Call a pure function on each of some values, splitting the calls across threads
+fn par_call1(
+    f: &Value,
+    values: &[Value],
+    env: &Uiua,
+    result_name: &'static str,
+) -> UiuaResult<Vec<Value>> {
+    par_ranges(env, values.len(), LOOP_CHUNK, |range| {
+        let mut env = env.thread_env().with_max_threads(1);
+        let mut outputs = Vec::with_capacity(range.len());
+        for val in &values[range] {
+            env.push(val.clone());
+            env.call(f.clone())?;
+            outputs.push(env.pop(result_name)?);
+        }
+        Ok(outputs)
+    })
+}
+
 fn each1_1(f: Value, xs: Value, env: &mut Uiua) -> UiuaResult {
-    let mut new_values = Vec::with_capacity(xs.flat_len());
     let mut new_shape = Shape::from(xs.shape());
-    let mut old_values = xs.into_flat_values();
-    for val in old_values.by_ref() {
-        env.push(val);
-        let broke = env.call_catch_break(f.clone())?;
-        new_values.push(env.pop("each's function result")?);
-        if broke {
-            for row in old_values {
-                new_values.push(row);
+    let new_values = if can_par_loop(&f, &xs, xs.flat_len(), env) {
+        let values: Vec<_> = xs.into_flat_values().collect();
+        par_call1(&f, &values, env, "each's function result")?
+    } else {
+        let mut new_values = Vec::with_capacity(xs.flat_len());
+        let mut old_values = xs.into_flat_values();
+        for val in old_values.by_ref() {
+            env.push(val);
+            let broke = env.call_catch_break(f.clone())?;
+            new_values.push(env.pop("each's function result")?);
+            if broke {
+                for row in old_values {
+                    new_values.push(row);
+                }
+                break;
             }
-            break;
         }
-    }
+        new_values
+    };
     let mut eached = Value::from_row_values(new_values, env)?;
     new_shape.extend_from_slice(&eached.shape()[1..]);
     *eached.shape_mut() = new_shape;
@@ -527,19 +675,25 @@ pub fn rows(env: &mut Uiua) -> UiuaResult {
 }
 
 fn rows1_1(f: Value, xs: Value, env: &mut Uiua) -> UiuaResult {
-    let mut new_rows = Vec::with_capacity(xs.row_count());
-    let mut old_rows = xs.into_rows();
-    for row in old_rows.by_ref() {
-        env.push(row);
-        let broke = env.call_catch_break(f.clone())?;
-        new_rows.push(env.pop("rows' function result")?);
-        if broke {
-            for row in old_rows {
-                new_rows.push(row);
+    let new_rows = if can_par_loop(&f, &xs, xs.row_count(), env) {
+        let rows: Vec<_> = xs.into_rows().collect();
+        par_call1(&f, &rows, env, "rows' function result")?
+    } else {
+        let mut new_rows = Vec::with_capacity(xs.row_count());
+        let mut old_rows = xs.into_rows();
+        for row in old_rows.by_ref() {
+            env.push(row);
+            let broke = env.call_catch_break(f.clone())?;
+            new_rows.push(env.pop("rows' function result")?);
+            if broke {
+                for row in old_rows {
+                    new_rows.push(row);
+                }
+                break;
             }
-            break;
         }
-    }
+        new_rows
+    };
     let res = Value::from_row_values(new_rows, env)?;
     env.push(res);
     Ok(())
@@ -633,6 +787,171 @@ fn rowsn_0(f: Value, args: Vec<Value>, env: &mut Uiua) -> UiuaResult {
     Ok(())
 }
 
+/// The size of the windows if the top of the stack is a scalar window size
+/// that fits in the array under it
+fn scalar_windows(env: &Uiua) -> Option<usize> {
+    let [.., xs, size] = env.stack.as_slice() else {
+        return None;
+    };
+    let size = match size {
+        Value::Num(n) if n.rank() == 0 && n.data[0].fract() == 0.0 && n.data[0] >= 1.0 => {
+            n.data[0] as usize
+        }
+        Value::Byte(n) if n.rank() == 0 && n.data[0] >= 1 => n.data[0] as usize,
+        _ => return None,
+    };
+    (xs.rank() >= 1 && size <= xs.row_count()).then_some(size)
+}
+
+/// Call a function on each window of an array without materializing all the windows
+///
+/// The window size and the array are taken from the stack.
+/// Returns `false` without touching the stack if they are not supported.
+pub fn rows_windows(f: &Value, env: &mut Uiua) -> UiuaResult<bool> {
+    if f.signature() != Signature::new(1, 1) {
+        return Ok(false);
+    }
+    let Some(size) = scalar_windows(env) else {
+        return Ok(false);
+    };
+    env.pop(ArrayArg(1))?;
+    let xs = env.pop(ArrayArg(2))?;
+    if let Some(res) = sliding_reduce(f, &xs, size) {
+        env.push(res);
+        return Ok(true);
+    }
+    let mut new_rows = Vec::with_capacity(xs.row_count() + 1 - size);
+    let mut windows = xs.row_windows(size);
+    for window in windows.by_ref() {
+        env.push(window);
+        let broke = env.call_catch_break(f.clone())?;
+        new_rows.push(env.pop("rows' function result")?);
+        if broke {
+            new_rows.extend(windows);
+            break;
+        }
+    }
+    let res = Value::from_row_values(new_rows, env)?;
+    env.push(res);
+    Ok(true)
+}
+
+/// Reduce the windows of an array without materializing them
+///
+/// The window size and the array are taken from the stack.
+/// Returns `false` without touching the stack if they are not supported.
+pub fn reduce_windows(f: &Value, env: &mut Uiua) -> UiuaResult<bool> {
+    if f.signature() != Signature::new(2, 1) {
+        return Ok(false);
+    }
+    let Some(size) = scalar_windows(env) else {
+        return Ok(false);
+    };
+    env.pop(ArrayArg(1))?;
+    let mut xs = env.pop(ArrayArg(2))?;
+    // The primitives that `reduce` has fast paths for always produce numbers
+    if let (Some((prim, _)), Value::Byte(bytes)) = (f.as_flipped_primitive(), &xs) {
+        use Primitive::*;
+        if matches!(prim, Add | Sub | Mul | Div | Max | Min) {
+            xs = Value::Num(bytes.convert_ref());
+        }
+    }
+    let mut windows = xs.row_windows(size);
+    let mut acc = windows.next().unwrap();
+    for window in windows {
+        env.push(window);
+        env.push(acc);
+        let broke = env.call_catch_break(f.clone())?;
+        acc = env.pop("reduced function result")?;
+        if broke {
+            break;
+        }
+    }
+    env.push(acc);
+    Ok(true)
+}
+
+/// The primitive that a function reduces a list with, if it is one that can be slid
+fn window_reducer(f: &Value) -> Option<Primitive> {
+    let f = f.as_function()?;
+    let prim = match f.instrs.as_slice() {
+        [Instr::Fused(Fused::Sum, _)] => Primitive::Add,
+        [Instr::Push(g), Instr::Prim(Primitive::Reduce, _)] => g.as_function()?.as_primitive()?.0,
+        _ => return None,
+    };
+    matches!(prim, Primitive::Add | Primitive::Max | Primitive::Min).then_some(prim)
+}
+
+/// Sums, maximums, or minimums of the windows of a list in linear time
+fn sliding_reduce(f: &Value, xs: &Value, size: usize) -> Option<Value> {
+    if xs.rank() != 1 {
+        return None;
+    }
+    let prim = window_reducer(f)?;
+    let nums: Vec<f64> = match xs {
+        Value::Num(arr) => arr.data.to_vec(),
+        Value::Byte(arr) => arr.data.iter().map(|&b| f64::from(b)).collect(),
+        _ => return None,
+    };
+    let f = match prim {
+        Primitive::Add => Add::add,
+        Primitive::Max => f64::max,
+        _ => f64::min,
+    };
+    // Sliding is only exact for integers whose sums cannot lose precision,
+    // and it may lose the sign of zeros, so other lists are reduced window by window
+    let max = nums.iter().fold(0.0, |max: f64, x| max.max(x.abs()));
+    let exact = (nums.iter()).all(|x| !(*x == 0.0 && x.is_sign_negative()))
+        && (prim != Primitive::Add
+            || max * size as f64 <= 2f64.powi(52) && nums.iter().all(|x| x.fract() == 0.0));
+    let reduced: Vec<f64> = if !exact {
+        (nums.windows(size))
+            .map(|window| window[1..].iter().copied().fold(window[0], f))
+            .collect()
+    } else {
+        match prim {
+            Primitive::Add => sliding_sum(&nums, size),
+            Primitive::Max => sliding_best(&nums, size, |a, b| a >= b),
+            _ => sliding_best(&nums, size, |a, b| a <= b),
+        }
+    };
+    Some(reduced.into())
+}
+
+fn sliding_sum(nums: &[f64], size: usize) -> Vec<f64> {
+    let mut sums = Vec::with_capacity(nums.len() + 1 - size);
+    let mut sum: f64 = nums[..size].iter().sum();
+    sums.push(sum);
+    for i in size..nums.len() {
+        sum += nums[i] - nums[i - size];
+        sums.push(sum);
+    }
+    sums
+}
+
+/// Slide a window with a queue of the indices of the values that could still be its best,
+/// ignoring NaNs unless a window is all NaN
+fn sliding_best(nums: &[f64], size: usize, better: impl Fn(f64, f64) -> bool) -> Vec<f64> {
+    let mut bests = Vec::with_capacity(nums.len() + 1 - size);
+    let mut queue = VecDeque::new();
+    for (i, &x) in nums.iter().enumerate() {
+        if !x.is_nan() {
+            while queue.back().is_some_and(|&j| better(x, nums[j])) {
+                queue.pop_back();
+            }
+            queue.push_back(i);
+        }
+        if i + 1 >= size {
+            let start = i + 1 - size;
+            while queue.front().is_some_and(|&j| j < start) {
+                queue.pop_front();
+            }
+            bests.push(queue.front().map_or(f64::NAN, |&j| nums[j]));
+        }
+    }
+    bests
+}
+
 pub fn distribute(env: &mut Uiua) -> UiuaResult {
     crate::profile_function!();
     let f = env.pop(FunctionArg(1))?;
@@ -776,13 +1095,13 @@ pub fn table(env: &mut Uiua) -> UiuaResult {
 }
 
 #[allow(clippy::result_large_err)]
-fn table_nums(
+pub(crate) fn table_nums<X: TableLeft<f64>>(
     prim: Primitive,
     flipped: bool,
-    xs: Array<f64>,
+    xs: X,
     ys: Array<f64>,
     env: &mut Uiua,
-) -> Result<(), (Array<f64>, Array<f64>)> {
+) -> Result<(), (X, Array<f64>)> {
     match prim {
         Primitive::Eq => env.push(fast_table(xs, ys, bin_bool(|x, y| x == y))),
         Primitive::Ne => env.push(fast_table(xs, ys, bin_bool(|x, y| x != y))),
@@ -808,31 +1127,46 @@ fn table_nums(
     Ok(())
 }
 
+/// The first argument of a fast table, which does not have to be materialized
+pub(crate) trait TableLeft<T> {
+    fn shape(&self) -> Shape;
+    fn elements(self) -> impl Iterator<Item = T>;
+}
+
+impl<T: ArrayValue> TableLeft<T> for Array<T> {
+    fn shape(&self) -> Shape {
+        self.shape.clone()
+    }
+    fn elements(self) -> impl Iterator<Item = T> {
+        self.data.into_iter()
+    }
+}
+
 fn fast_table<A: ArrayValue, B: ArrayValue, C: ArrayValue>(
-    a: Array<A>,
+    a: impl TableLeft<A>,
     b: Array<B>,
     f: impl Fn(A, B) -> C,
 ) -> Array<C> {
-    let mut new_data = Vec::with_capacity(a.data.len() * b.data.len());
-    for x in a.data {
+    let mut new_shape = a.shape();
+    let mut new_data = Vec::with_capacity(new_shape.iter().product::<usize>() * b.data.len());
+    for x in a.elements() {
         for y in b.data.iter().cloned() {
             new_data.push(f(x.clone(), y));
         }
     }
-    let mut new_shape = a.shape;
     new_shape.extend_from_slice(&b.shape);
     Array::new(new_shape, new_data)
 }
 
-fn fast_table_join_or_couple<T: ArrayValue>(a: Array<T>, b: Array<T>) -> Array<T> {
-    let mut new_data = Vec::with_capacity(a.data.len() * b.data.len() * 2);
-    for x in a.data {
+fn fast_table_join_or_couple<T: ArrayValue>(a: impl TableLeft<T>, b: Array<T>) -> Array<T> {
+    let mut new_shape = a.shape();
+    let mut new_data = Vec::with_capacity(new_shape.iter().product::<usize>() * b.data.len() * 2);
+    for x in a.elements() {
         for y in b.data.iter().cloned() {
             new_data.push(x.clone());
             new_data.push(y);
         }
     }
-    let mut new_shape = a.shape;
     new_shape.extend_from_slice(&b.shape);
     new_shape.push(2);
     Array::new(new_shape, new_data)
@@ -1203,6 +1537,11 @@ impl Value {
                 .partition_groups(markers, env)?
                 .map(Into::into)
                 .collect(),
+            Value::Bits(bits) => bits
+                .to_bytes()
+                .partition_groups(markers, env)?
+                .map(Into::into)
+                .collect(),
         })
     }
 }
@@ -1252,6 +1591,11 @@ impl Value {
             Value::Byte(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Char(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
             Value::Func(arr) => arr.group_groups(indices, env)?.map(Into::into).collect(),
+            Value::Bits(bits) => bits
+                .to_bytes()
+                .group_groups(indices, env)?
+                .map(Into::into)
+                .collect(),
         })
     }
 }
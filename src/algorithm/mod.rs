@@ -10,8 +10,10 @@ use crate::{
 mod dyadic;
 pub mod fork;
 pub(crate) mod invert;
+pub mod lazy;
 pub mod loops;
 mod monadic;
+pub(crate) mod par;
 pub mod pervade;
 
 fn max_shape(a: &[usize], b: &[usize]) -> Shape {
@@ -0,0 +1,70 @@
+//! Helpers for splitting array operations across threads
+
+use std::{convert::Infallible, ops::Range, thread};
+
+use crate::Uiua;
+
+/// The minimum number of elements each thread gets in a pervasive operation
+pub const PERVADE_CHUNK: usize = 1 << 14;
+/// The minimum number of rows each thread gets in a looping modifier
+pub const LOOP_CHUNK: usize = 64;
+
+/// Build a result out of contiguous index ranges, splitting the ranges across threads
+///
+/// Each thread gets at least `min_chunk` indices. If there would only be one thread,
+/// `f` is called once on the current thread with the whole range.
+///
+/// The outputs are concatenated in index order, and if any range fails,
+/// the error from the earliest one is returned, so the result is the same
+/// as a single serial call.
+pub fn par_ranges<T, E>(
+    env: &Uiua,
+    len: usize,
+    min_chunk: usize,
+    f: impl Fn(Range<usize>) -> Result<Vec<T>, E> + Sync,
+) -> Result<Vec<T>, E>
+where
+    T: Send,
+    E: Send,
+{
+    let threads = env.max_threads().min(len / min_chunk.max(1));
+    if threads <= 1 {
+        return f(0..len);
+    }
+    let chunk_len = len.div_ceil(threads);
+    let f = &f;
+    let results: Vec<Result<Vec<T>, E>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..len)
+            .step_by(chunk_len)
+            .map(|start| scope.spawn(move || f(start..(start + chunk_len).min(len))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    });
+    let mut output = Vec::with_capacity(len);
+    for result in results {
+        output.extend(result?);
+    }
+    Ok(output)
+}
+
+/// Map a function over a slice, splitting the work across threads if it is large enough
+pub fn par_map<T, U>(env: &Uiua, items: &[T], f: impl Fn(T) -> U + Sync) -> Vec<U>
+where
+    T: Clone + Sync,
+    U: Send,
+{
+    let res = par_ranges(env, items.len(), PERVADE_CHUNK, |range| {
+        Ok::<_, Infallible>(items[range].iter().cloned().map(&f).collect())
+    });
+    match res {
+        Ok(output) => output,
+        Err(e) => match e {},
+    }
+}
@@ -10,7 +10,10 @@ use std::{
 
 use crate::{array::*, Uiua, UiuaError, UiuaResult};
 
-use super::max_shape;
+use super::{
+    max_shape,
+    par::{par_ranges, PERVADE_CHUNK},
+};
 
 #[allow(clippy::len_without_is_empty)]
 pub trait Arrayish {
@@ -81,6 +84,28 @@ pub trait PervasiveFn<A, B> {
     type Output;
     type Error;
     fn call(&self, a: A, b: B, env: &Uiua) -> Result<Self::Output, Self::Error>;
+    /// Call the function on corresponding elements of two slices
+    ///
+    /// A slice with a single element is paired with every element of the other.
+    fn call_slices(
+        &self,
+        a: &[A],
+        b: &[B],
+        c: &mut Vec<Self::Output>,
+        env: &Uiua,
+    ) -> Result<(), Self::Error>
+    where
+        A: Clone,
+        B: Clone,
+    {
+        let len = if a.len() == 1 { b.len() } else { a.len() };
+        let a_step = (a.len() != 1) as usize;
+        let b_step = (b.len() != 1) as usize;
+        for i in 0..len {
+            c.push(self.call(a[i * a_step].clone(), b[i * b_step].clone(), env)?);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -101,6 +126,21 @@ where
     fn call(&self, a: A, b: B, _env: &Uiua) -> Result<Self::Output, Self::Error> {
         Ok((self.0)(a, b))
     }
+    // Extending from iterators over slices, without indexing or checking for errors,
+    // lets simple numeric functions compile to vectorized loops
+    fn call_slices(&self, a: &[A], b: &[B], c: &mut Vec<C>, _env: &Uiua) -> Result<(), Infallible>
+    where
+        A: Clone,
+        B: Clone,
+    {
+        let f = &self.0;
+        match (a, b) {
+            ([a], b) if b.len() != 1 => c.extend(b.iter().map(|b| f(a.clone(), b.clone()))),
+            (a, [b]) => c.extend(a.iter().map(|a| f(a.clone(), b.clone()))),
+            (a, b) => c.extend(a.iter().zip(b).map(|(a, b)| f(a.clone(), b.clone()))),
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -128,8 +168,8 @@ where
     A: ArrayValue,
     B: ArrayValue,
     C: ArrayValue,
-    F: PervasiveFn<A, B, Output = C> + Clone,
-    F::Error: Into<UiuaError>,
+    F: PervasiveFn<A, B, Output = C> + Clone + Sync,
+    F::Error: Into<UiuaError> + Send,
 {
     let mut a = a;
     let mut b = b;
@@ -209,8 +249,25 @@ where
         }
     }
     let shape = Shape::from(a.shape().max(b.shape()));
-    let mut data = Vec::with_capacity(a.flat_len().max(b.flat_len()));
-    bin_pervade_recursive(a, b, &mut data, env, f).map_err(Into::into)?;
+    let data = match (a.shape(), b.shape()) {
+        // Elementwise cases are split across threads if they are large enough
+        (ash, bsh) if ash == bsh || ash.is_empty() || bsh.is_empty() => {
+            let (a, b) = (a.data(), b.data());
+            par_ranges(env, shape.iter().product(), PERVADE_CHUNK, |range| {
+                let a = if ash.is_empty() { a } else { &a[range.clone()] };
+                let b = if bsh.is_empty() { b } else { &b[range.clone()] };
+                let mut data = Vec::with_capacity(range.len());
+                f.call_slices(a, b, &mut data, env)?;
+                Ok::<_, F::Error>(data)
+            })
+            .map_err(Into::into)?
+        }
+        _ => {
+            let mut data = Vec::with_capacity(a.flat_len().max(b.flat_len()));
+            bin_pervade_recursive(a, b, &mut data, env, f).map_err(Into::into)?;
+            data
+        }
+    };
     Ok(Array::new(shape, data))
 }
 
@@ -229,11 +286,7 @@ where
 {
     match (a.shape(), b.shape()) {
         ([], []) => c.push(f.call(a.data()[0].clone(), b.data()[0].clone(), env)?),
-        (ash, bsh) if ash == bsh => {
-            for (a, b) in a.data().iter().zip(b.data()) {
-                c.push(f.call(a.clone(), b.clone(), env)?);
-            }
-        }
+        (ash, bsh) if ash == bsh => f.call_slices(a.data(), b.data(), c, env)?,
         ([], bsh) => {
             for brow in b.rows() {
                 bin_pervade_recursive(a, &(&bsh[1..], brow), c, env, f.clone())?;
@@ -12,6 +12,7 @@ pub enum Item {
     Scoped { items: Vec<Item>, test: bool },
     Words(Vec<Sp<Word>>),
     Binding(Binding),
+    Import(Import),
     ExtraNewlines(CodeSpan),
 }
 
@@ -20,6 +21,22 @@ pub struct Binding {
     pub name: Sp<Ident>,
     pub signature: Option<Sp<Signature>>,
     pub words: Vec<Sp<Word>>,
+    /// Whether the binding is hidden from files that import this one
+    pub private: bool,
+}
+
+/// A module import
+///
+/// `Name ~ "path"` makes the module's bindings available as `Name~Binding`.
+/// `~ "path" ~ A B` brings the bindings `A` and `B` into scope directly.
+#[derive(Debug, Clone)]
+pub struct Import {
+    /// The prefix for qualified names
+    pub name: Option<Sp<Ident>>,
+    /// The path of the file to import
+    pub path: Sp<String>,
+    /// The names to bring into scope directly
+    pub items: Vec<Sp<Ident>>,
 }
 
 #[derive(Clone)]
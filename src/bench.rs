@@ -0,0 +1,185 @@
+//! Benchmarking user code
+//!
+//! A benchmark is a binding whose name starts with `Bench` and that takes no arguments.
+//! When benchmarks are enabled with [`Uiua::with_benches`](crate::Uiua::with_benches),
+//! the code of each one is kept so that it can be run many times by
+//! [`Uiua::run_benches`](crate::Uiua::run_benches).
+//!
+//! ```uiua
+//! BenchSort ← ⍏ ⇌⇡10000
+//! ```
+
+use std::{collections::BTreeMap, fs, io, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{function::Instr, lex::CodeSpan, Ident, UiuaError, UiuaResult};
+
+/// The code of a benchmark
+#[derive(Debug, Clone)]
+pub(crate) struct Bench {
+    pub name: Ident,
+    pub span: CodeSpan,
+    pub instrs: Vec<Instr>,
+}
+
+/// Whether a binding is a benchmark
+pub(crate) fn is_bench(name: &str) -> bool {
+    name.starts_with("Bench")
+}
+
+/// How benchmarks are run
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// The number of runs of each benchmark that are not measured
+    pub warmup: usize,
+    /// The number of measured runs of each benchmark
+    pub runs: usize,
+    /// Only benchmarks whose names contain this are run
+    pub filter: Option<String>,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            warmup: 3,
+            runs: 20,
+            filter: None,
+        }
+    }
+}
+
+/// Statistics about the runs of a benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BenchStats {
+    #[serde(with = "secs")]
+    pub mean: Duration,
+    #[serde(with = "secs")]
+    pub median: Duration,
+    #[serde(with = "secs")]
+    pub stddev: Duration,
+}
+
+impl BenchStats {
+    /// Compute statistics from the durations of runs
+    pub fn new(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return BenchStats {
+                mean: Duration::ZERO,
+                median: Duration::ZERO,
+                stddev: Duration::ZERO,
+            };
+        }
+        let mut secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+        secs.sort_by(f64::total_cmp);
+        let n = secs.len();
+        let mean = secs.iter().sum::<f64>() / n as f64;
+        let median = if n % 2 == 1 {
+            secs[n / 2]
+        } else {
+            (secs[n / 2 - 1] + secs[n / 2]) / 2.0
+        };
+        let variance = if n > 1 {
+            secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        BenchStats {
+            mean: Duration::from_secs_f64(mean),
+            median: Duration::from_secs_f64(median),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// The outcome of running a benchmark
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub name: Ident,
+    pub span: CodeSpan,
+    /// The duration of each measured run
+    pub samples: Vec<Duration>,
+    pub stats: BenchStats,
+}
+
+/// Saved benchmark statistics that later runs are compared against
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Baseline {
+    pub benches: BTreeMap<String, BenchStats>,
+}
+
+impl Baseline {
+    /// Make a baseline from the results of a run
+    pub fn new(results: &[BenchResult]) -> Self {
+        let benches = (results.iter())
+            .map(|res| (res.name.to_string(), res.stats))
+            .collect();
+        Baseline { benches }
+    }
+    /// Load a baseline from a file
+    pub fn load(path: &Path) -> UiuaResult<Self> {
+        let json = fs::read_to_string(path).map_err(|e| UiuaError::Load(path.into(), e.into()))?;
+        serde_json::from_str(&json).map_err(|e| {
+            UiuaError::Load(
+                path.into(),
+                io::Error::new(io::ErrorKind::InvalidData, e).into(),
+            )
+        })
+    }
+    /// Save the baseline to a file
+    pub fn save(&self, path: &Path) -> UiuaResult {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, json).map_err(|e| UiuaError::Load(path.into(), e.into()))
+    }
+    /// Compare results against the baseline
+    ///
+    /// A benchmark has regressed if its median is more than `threshold` percent slower.
+    /// Benchmarks that are not in the baseline are not compared.
+    pub fn compare(&self, results: &[BenchResult], threshold: f64) -> Vec<BenchComparison> {
+        let mut comparisons = Vec::new();
+        for res in results {
+            let Some(base) = self.benches.get(res.name.as_ref()) else {
+                continue;
+            };
+            let (before, after) = (base.median.as_secs_f64(), res.stats.median.as_secs_f64());
+            let change = if before == 0.0 {
+                0.0
+            } else {
+                (after - before) / before * 100.0
+            };
+            comparisons.push(BenchComparison {
+                name: res.name.clone(),
+                baseline: base.median,
+                current: res.stats.median,
+                change,
+                regressed: change > threshold,
+            });
+        }
+        comparisons
+    }
+}
+
+/// How a benchmark's median changed from a baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchComparison {
+    pub name: Ident,
+    pub baseline: Duration,
+    pub current: Duration,
+    /// The change in percent, positive if the benchmark got slower
+    pub change: f64,
+    pub regressed: bool,
+}
+
+mod secs {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64())
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Ok(Duration::from_secs_f64(secs.max(0.0)))
+    }
+}
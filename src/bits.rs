@@ -0,0 +1,169 @@
+//! Bit-packed boolean arrays
+//!
+//! Comparisons produce masks, and masks are often as long as the arrays they filter.
+//! Storing them with one bit per element instead of one byte keeps large filters small.
+//! Primitives that do not know about [`BitArray`]s see them as byte arrays.
+
+use std::fmt;
+
+use ecow::EcoVec;
+
+use crate::array::{Array, ArrayValue, FormatShape, Shape};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// An array of booleans stored as bits
+#[derive(Clone, Default)]
+pub struct BitArray {
+    shape: Shape,
+    words: EcoVec<u64>,
+}
+
+impl BitArray {
+    /// Pack the results of a predicate on each element of a list
+    pub fn from_slice<T: Copy>(shape: Shape, data: &[T], f: impl Fn(T) -> bool) -> Self {
+        debug_assert_eq!(data.len(), shape.iter().product::<usize>());
+        let words = (data.chunks(WORD_BITS))
+            .map(|chunk| {
+                (chunk.iter().enumerate()).fold(0u64, |word, (i, &x)| word | (f(x) as u64) << i)
+            })
+            .collect();
+        Self::new(shape, words)
+    }
+    /// Pack the results of a predicate on corresponding elements of two lists of the same length
+    pub fn from_slices<A: Copy, B: Copy>(
+        shape: Shape,
+        a: &[A],
+        b: &[B],
+        f: impl Fn(A, B) -> bool,
+    ) -> Self {
+        debug_assert_eq!(a.len(), b.len());
+        debug_assert_eq!(a.len(), shape.iter().product::<usize>());
+        let words = (a.chunks(WORD_BITS).zip(b.chunks(WORD_BITS)))
+            .map(|(a, b)| {
+                (a.iter().zip(b).enumerate())
+                    .fold(0u64, |word, (i, (&a, &b))| word | (f(a, b) as u64) << i)
+            })
+            .collect();
+        Self::new(shape, words)
+    }
+    fn new(shape: Shape, words: EcoVec<u64>) -> Self {
+        BitArray { shape, words }
+    }
+    /// Pack a byte array, if all of its elements are booleans
+    pub fn from_bytes(arr: &Array<u8>) -> Option<Self> {
+        if arr.data.iter().any(|&b| b > 1) {
+            return None;
+        }
+        Some(Self::from_slice(arr.shape.clone(), &arr.data, |b| b == 1))
+    }
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+    pub fn format_shape(&self) -> FormatShape<'_> {
+        FormatShape(&self.shape)
+    }
+    pub fn rank(&self) -> usize {
+        self.shape.len()
+    }
+    pub fn row_count(&self) -> usize {
+        self.shape.first().copied().unwrap_or(1)
+    }
+    pub fn flat_len(&self) -> usize {
+        self.shape.iter().product()
+    }
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+    /// Iterate over the indices of the set bits
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.words.iter().enumerate()).flat_map(|(w, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    w * WORD_BITS + bit
+                })
+            })
+        })
+    }
+    /// The number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+    /// Flip every bit
+    pub fn not(&self) -> Self {
+        let mut words: EcoVec<u64> = self.words.iter().map(|w| !w).collect();
+        // Bits past the end must stay clear for counting
+        let tail = self.flat_len() % WORD_BITS;
+        if tail > 0 {
+            let last = words.make_mut().last_mut().unwrap();
+            *last &= (1 << tail) - 1;
+        }
+        Self::new(self.shape.clone(), words)
+    }
+    /// Keep the rows of an array whose bits are set
+    ///
+    /// The bit array must be a list with one bit per row.
+    pub fn keep<T: ArrayValue>(&self, arr: &Array<T>) -> Array<T> {
+        debug_assert_eq!(self.rank(), 1);
+        debug_assert_eq!(self.flat_len(), arr.row_count());
+        let row_len = arr.row_len();
+        let count = self.count_ones();
+        let mut data = EcoVec::with_capacity(count * row_len);
+        for i in self.ones() {
+            data.extend_from_slice(&arr.data[i * row_len..(i + 1) * row_len]);
+        }
+        let mut shape = arr.shape.clone();
+        shape[0] = count;
+        Array::new(shape, data)
+    }
+    /// Unpack the bits into a new byte array
+    ///
+    /// The byte array is not kept, so unpacking again does the work again.
+    pub fn to_bytes(&self) -> Array<u8> {
+        self.unpack()
+    }
+    /// Convert the bits into a byte array
+    pub fn into_bytes(self) -> Array<u8> {
+        self.unpack()
+    }
+    fn unpack(&self) -> Array<u8> {
+        let len = self.flat_len();
+        let mut data = Vec::with_capacity(len);
+        for (w, &word) in self.words.iter().enumerate() {
+            let bits = WORD_BITS.min(len - w * WORD_BITS);
+            data.extend((0..bits).map(|bit| (word >> bit & 1) as u8));
+        }
+        Array::new(self.shape.clone(), data)
+    }
+}
+
+impl fmt::Debug for BitArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_bytes().fmt(f)
+    }
+}
+
+#[test]
+fn bit_array_is_no_bigger_than_array() {
+    assert!(std::mem::size_of::<BitArray>() <= std::mem::size_of::<Array<u8>>());
+}
+
+#[test]
+fn bit_array_ops() {
+    let shape = Shape::from([130].as_slice());
+    let indices: Vec<usize> = (0..130).collect();
+    let bits = BitArray::from_slice(shape, &indices, |i| i % 3 == 0);
+    assert_eq!(bits.count_ones(), 44);
+    assert_eq!(bits.ones().take(3).collect::<Vec<_>>(), [0, 3, 6]);
+    let not = bits.not();
+    assert_eq!(not.count_ones(), 86);
+    assert!(!not.get(129) && not.get(128));
+    let bytes = not.to_bytes();
+    assert_eq!(bytes.data.iter().filter(|&&b| b == 1).count(), 86);
+    assert_eq!(BitArray::from_bytes(&bytes).unwrap().count_ones(), 86);
+    let nums: Array<f64> = (0..130).map(|i| i as f64).collect();
+    assert_eq!(bits.keep(&nums).data.last(), Some(&129.0));
+}
@@ -0,0 +1,470 @@
+//! Building standalone executables and single-file bundles from Uiua programs
+//!
+//! A built executable is a copy of the interpreter with the program's sources
+//! appended to it, followed by the length of the sources and a magic number.
+//!
+//! A bundle is a single file with every imported file inlined.
+//! Each imported file becomes a scope followed by a binding for each value it leaves on the stack,
+//! and its imports are replaced with those bindings.
+//! The number of values is inferred from the signatures of the file's lines.
+//!
+//! Files that use namespaced imports, or imported files with non-test scopes,
+//! cannot be bundled.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{Binding, Item, Word},
+    function::Signature,
+    lex::{CodeSpan, Sp, Span},
+    package::{resolve_import, Package},
+    parse::parse,
+    primitive::Primitive,
+    run::RunMode,
+    Ident, SysOp, Uiua, UiuaError, UiuaResult,
+};
+
+const MAGIC: &[u8; 8] = b"UIUAEXE\0";
+const TRAILER_LEN: usize = 8 + MAGIC.len();
+
+/// A program embedded in an executable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddedProgram {
+    /// The path of the main file
+    pub main: String,
+    /// The sources of all files, keyed by their resolved paths
+    pub files: BTreeMap<String, String>,
+}
+
+impl EmbeddedProgram {
+    /// Collect a program's main file and every file it imports, recursively
+    ///
+    /// Only imports of string literals can be followed.
+    /// The spans of any other imports are returned along with the program.
+    pub fn collect(main: &Path, package: Option<&Package>) -> UiuaResult<(Self, Vec<CodeSpan>)> {
+        let main_key = main.to_string_lossy().into_owned();
+        let mut program = EmbeddedProgram {
+            main: main_key.clone(),
+            files: BTreeMap::new(),
+        };
+        let mut dynamic = Vec::new();
+        let mut queue = vec![(main_key, main.to_path_buf())];
+        while let Some((key, path)) = queue.pop() {
+            if program.files.contains_key(&key) {
+                continue;
+            }
+            let input = match fs::read_to_string(&path) {
+                Ok(input) => input,
+                // The interpreter provides a fallback for the example file
+                Err(_) if key == "example.ua" => continue,
+                Err(e) => return Err(UiuaError::Load(path, e.into())),
+            };
+            let (items, errors, _) = parse(&input, Some(&path));
+            if !errors.is_empty() {
+                return Err(errors.into());
+            }
+            let (literal, other) = import_paths(&items);
+            dynamic.extend(other);
+            for import in literal.into_iter().rev() {
+                let resolved = resolve_import(package, Some(&path), &import.value);
+                queue.push((resolved.to_string_lossy().into_owned(), resolved));
+            }
+            program.files.insert(key, input);
+        }
+        Ok((program, dynamic))
+    }
+    /// Write an executable that runs this program
+    ///
+    /// `interpreter` is the path of the interpreter binary to copy.
+    pub fn write_executable(&self, interpreter: &Path, output: &Path) -> io::Result<()> {
+        let mut bytes = fs::read(interpreter)?;
+        if let Some(payload_len) = payload_len(&bytes[bytes.len().saturating_sub(TRAILER_LEN)..]) {
+            bytes.truncate(bytes.len() - TRAILER_LEN - payload_len);
+        }
+        let payload = toml::to_string(self).expect("program should serialize");
+        bytes.extend(payload.as_bytes());
+        bytes.extend((payload.len() as u64).to_le_bytes());
+        bytes.extend(MAGIC);
+        fs::write(output, bytes)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(output, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    }
+    /// Read the program embedded in an executable, if there is one
+    pub fn from_executable(path: &Path) -> Option<Self> {
+        let mut file = File::open(path).ok()?;
+        file.seek(SeekFrom::End(-(TRAILER_LEN as i64))).ok()?;
+        let mut trailer = [0; TRAILER_LEN];
+        file.read_exact(&mut trailer).ok()?;
+        let payload_len = payload_len(&trailer)?;
+        file.seek(SeekFrom::End(-((TRAILER_LEN + payload_len) as i64)))
+            .ok()?;
+        let mut payload = vec![0; payload_len];
+        file.read_exact(&mut payload).ok()?;
+        toml::from_str(std::str::from_utf8(&payload).ok()?).ok()
+    }
+}
+
+fn payload_len(trailer: &[u8]) -> Option<usize> {
+    let (len, magic) = trailer.split_at(trailer.len().checked_sub(MAGIC.len())?);
+    if magic != MAGIC || len.len() != 8 {
+        return None;
+    }
+    Some(u64::from_le_bytes(len.try_into().unwrap()) as usize)
+}
+
+/// Inline all the files a file imports
+///
+/// Only imports of string literals that make up a whole line, like `&i "lib.ua"`
+/// or `Lib ← &i "lib.ua"`, can be inlined.
+pub fn bundle(main: &Path, package: Option<&Package>) -> UiuaResult<Vec<Item>> {
+    let mut bundler = Bundler {
+        package,
+        checker: Uiua::with_native_sys().with_mode(RunMode::Check),
+        output: Vec::new(),
+        names: HashMap::new(),
+        used_names: HashSet::new(),
+        current: Vec::new(),
+    };
+    bundler.current.push(main.to_path_buf());
+    let input = fs::read_to_string(main).map_err(|e| UiuaError::Load(main.into(), e.into()))?;
+    bundler.file_items(&input, main, true)?;
+    Ok(bundler.output)
+}
+
+struct Bundler<'a> {
+    package: Option<&'a Package>,
+    /// Used to infer how many values inlined files leave on the stack
+    checker: Uiua,
+    /// The top-level items of the bundle
+    output: Vec<Item>,
+    /// The names bound to the values of files that have been inlined
+    names: HashMap<PathBuf, Vec<Ident>>,
+    used_names: HashSet<Ident>,
+    /// The files currently being inlined (used to detect import cycles)
+    current: Vec<PathBuf>,
+}
+
+impl<'a> Bundler<'a> {
+    /// Replace a file's imports and either add its items to the output or return them
+    fn file_items(&mut self, input: &str, path: &Path, top_level: bool) -> UiuaResult<Vec<Item>> {
+        let (items, errors, _) = parse(input, Some(path));
+        if !errors.is_empty() {
+            return Err(errors.into());
+        }
+        let mut file_items = Vec::new();
+        for item in items {
+            let item = match item {
+                // Test scopes in imported files are never run
+                Item::Scoped { test: true, .. } if !top_level => continue,
+                Item::Scoped { items, test } if top_level => {
+                    let mut scope_items = Vec::new();
+                    for item in items {
+                        scope_items.push(self.item(item)?);
+                    }
+                    Item::Scoped {
+                        items: scope_items,
+                        test,
+                    }
+                }
+                Item::Scoped { .. } => {
+                    return Err(UiuaError::Load(
+                        path.into(),
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Non-test scopes in imported files cannot be bundled",
+                        )
+                        .into(),
+                    ))
+                }
+                item => self.item(item)?,
+            };
+            if top_level {
+                self.output.push(item);
+            } else {
+                file_items.push(item);
+            }
+        }
+        Ok(file_items)
+    }
+    /// Replace an item's import, if it has one
+    fn item(&mut self, item: Item) -> UiuaResult<Item> {
+        if let Item::Import(import) = &item {
+            return Err(bundle_error(
+                import.path.span.clone(),
+                "Namespaced imports cannot be bundled",
+            ));
+        }
+        if let Some((binding, path)) = import_line(&item) {
+            let names = self.dependency(&path)?;
+            let span = path.span.clone();
+            // The last value is the top of the stack
+            let mut words = Vec::new();
+            for name in names.into_iter().rev() {
+                if !words.is_empty() {
+                    words.push(span.clone().sp(Word::Spaces));
+                }
+                words.push(span.clone().sp(Word::Ident(name)));
+            }
+            return Ok(match binding {
+                Some(binding) => Item::Binding(Binding { words, ..binding }),
+                None => Item::Words(words),
+            });
+        }
+        let (literal, dynamic) = import_paths(std::slice::from_ref(&item));
+        if let Some(span) = (literal.into_iter().map(|path| path.span))
+            .chain(dynamic)
+            .next()
+        {
+            return Err(bundle_error(
+                span,
+                "Only imports of string literals on their own line can be bundled",
+            ));
+        }
+        Ok(item)
+    }
+    /// Inline an imported file if it has not been already,
+    /// and get the names bound to the values it leaves on the stack
+    fn dependency(&mut self, path: &Sp<String>) -> UiuaResult<Vec<Ident>> {
+        let resolved = resolve_import(self.package, path.span.path.as_deref(), &path.value);
+        if let Some(names) = self.names.get(&resolved) {
+            return Ok(names.clone());
+        }
+        if self.current.contains(&resolved) {
+            return Err(bundle_error(
+                path.span.clone(),
+                format!("Cycle detected importing {}", resolved.to_string_lossy()),
+            ));
+        }
+        let input = match fs::read_to_string(&resolved) {
+            Ok(input) => input,
+            Err(_) if path.value == "example.ua" => crate::example_ua(|ex| ex.clone()),
+            Err(e) => return Err(UiuaError::Load(resolved, e.into())),
+        };
+        self.current.push(resolved.clone());
+        let items = self.file_items(&input, &resolved, false)?;
+        self.current.pop();
+        let Some(height) = self.checker.items_stack_height(items.clone())? else {
+            return Err(bundle_error(
+                path.span.clone(),
+                format!(
+                    "Cannot infer how many values {} leaves on the stack, \
+                    so it cannot be bundled",
+                    resolved.to_string_lossy()
+                ),
+            ));
+        };
+        let names: Vec<Ident> = (0..height).map(|_| self.binding_name(&resolved)).collect();
+        self.output.push(Item::Scoped { items, test: false });
+        // Each binding takes the value on top of the stack
+        for name in names.iter().rev() {
+            self.output.push(Item::Binding(Binding {
+                name: path.span.clone().sp(name.clone()),
+                signature: None,
+                words: Vec::new(),
+                private: false,
+            }));
+            (self.checker).bind_signature(name.clone(), Signature::new(0, 1));
+        }
+        self.names.insert(resolved, names.clone());
+        Ok(names)
+    }
+    /// Make a unique binding name for an imported file
+    fn binding_name(&mut self, path: &Path) -> Ident {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut name: String = "Bundled".into();
+        for word in stem.split(|c: char| !c.is_alphabetic()) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.extend(first.to_uppercase());
+                name.push_str(chars.as_str());
+            }
+        }
+        let mut unique = name.clone();
+        let mut suffix = 'A';
+        while self.used_names.contains(unique.as_str()) {
+            unique = format!("{name}{suffix}");
+            suffix = (suffix as u8 + 1) as char;
+        }
+        let unique: Ident = unique.into();
+        self.used_names.insert(unique.clone());
+        unique
+    }
+}
+
+/// Get the binding and path of an item that is only an import of a string literal
+fn import_line(item: &Item) -> Option<(Option<Binding>, Sp<String>)> {
+    let (binding, words) = match item {
+        Item::Words(words) => (None, words),
+        Item::Binding(binding) => (Some(binding.clone()), &binding.words),
+        _ => return None,
+    };
+    let mut words = (words.iter()).filter(|word| !matches!(word.value, Word::Spaces));
+    let (Some(import), Some(path), None) = (words.next(), words.next(), words.next()) else {
+        return None;
+    };
+    match (&import.value, &path.value) {
+        (Word::Primitive(Primitive::Sys(SysOp::Import)), Word::String(s)) => {
+            Some((binding, path.span.clone().sp(s.clone())))
+        }
+        _ => None,
+    }
+}
+
+fn bundle_error(span: CodeSpan, message: impl Into<String>) -> UiuaError {
+    UiuaError::Run(Span::Code(span).sp(message.into()))
+}
+
+/// Find the paths of all imports in some items
+///
+/// Returns the paths of imports of string literals,
+/// and the spans of imports whose paths are not known until runtime.
+pub fn import_paths(items: &[Item]) -> (Vec<Sp<String>>, Vec<CodeSpan>) {
+    let mut literal = Vec::new();
+    let mut dynamic = Vec::new();
+    for item in items {
+        match item {
+            Item::Scoped { items, .. } => {
+                let (l, d) = import_paths(items);
+                literal.extend(l);
+                dynamic.extend(d);
+            }
+            Item::Words(words) => words_import_paths(words, &mut literal, &mut dynamic),
+            Item::Binding(binding) => {
+                words_import_paths(&binding.words, &mut literal, &mut dynamic)
+            }
+            Item::Import(import) => literal.push(import.path.clone()),
+            Item::ExtraNewlines(_) => {}
+        }
+    }
+    (literal, dynamic)
+}
+
+fn words_import_paths(
+    words: &[Sp<Word>],
+    literal: &mut Vec<Sp<String>>,
+    dynamic: &mut Vec<CodeSpan>,
+) {
+    for (i, word) in words.iter().enumerate() {
+        match &word.value {
+            Word::Primitive(Primitive::Sys(SysOp::Import)) => {
+                let arg = words[i + 1..]
+                    .iter()
+                    .find(|word| !matches!(word.value, Word::Spaces));
+                match arg.map(|arg| (&arg.value, &arg.span)) {
+                    Some((Word::String(path), span)) => literal.push(span.clone().sp(path.clone())),
+                    _ => dynamic.push(word.span.clone()),
+                }
+            }
+            Word::Strand(items) => words_import_paths(items, literal, dynamic),
+            Word::Array(arr) => {
+                for line in &arr.lines {
+                    words_import_paths(line, literal, dynamic);
+                }
+            }
+            Word::Func(func) => {
+                for line in &func.lines {
+                    words_import_paths(line, literal, dynamic);
+                }
+            }
+            Word::Modified(modified) => words_import_paths(&modified.operands, literal, dynamic),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embed_roundtrip() {
+        let (items, _, _) = parse(
+            "&i \"a.ua\"\nF ← (&i \"b.ua\")\nLib ~ \"c.ua\"\n&i ⊂\"d\" \".ua\"",
+            None,
+        );
+        let (literal, dynamic) = import_paths(&items);
+        let literal: Vec<_> = literal.into_iter().map(|path| path.value).collect();
+        assert_eq!(literal, ["a.ua", "b.ua", "c.ua"]);
+        assert_eq!(dynamic.len(), 1);
+
+        let dir = std::env::temp_dir();
+        let interpreter = dir.join(format!("uiua-build-interpreter-{}", std::process::id()));
+        let output = dir.join(format!("uiua-build-output-{}", std::process::id()));
+        fs::write(&interpreter, b"not really an interpreter").unwrap();
+        let program = EmbeddedProgram {
+            main: "main.ua".into(),
+            files: [("main.ua".into(), "+1 2".into())].into(),
+        };
+        program.write_executable(&interpreter, &output).unwrap();
+        assert!(EmbeddedProgram::from_executable(&interpreter).is_none());
+        let read = EmbeddedProgram::from_executable(&output).unwrap();
+        assert_eq!(read.files, program.files);
+        // Building from a built executable replaces its program
+        program.write_executable(&output, &interpreter).unwrap();
+        let len = fs::metadata(&interpreter).unwrap().len();
+        assert_eq!(len, fs::metadata(&output).unwrap().len());
+
+        fs::remove_file(interpreter).unwrap();
+        fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn bundle_matches_imports() {
+        use crate::{format::*, Uiua};
+
+        let dir = std::env::temp_dir().join(format!("uiua-bundle-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        fs::write(path("util.ua"), "# Util\n5").unwrap();
+        fs::write(
+            path("lib.ua"),
+            format!("Sq ← ×.\nU ← &i {:?}\nSq_U", path("util.ua")),
+        )
+        .unwrap();
+        let main = format!(
+            "Lib ← &i {lib:?}\nSq ← use \"Sq\" Lib\nLibB ← &i {lib:?}\nSq 3",
+            lib = path("lib.ua")
+        );
+        fs::write(path("main.ua"), &main).unwrap();
+
+        let items = bundle(Path::new(&path("main.ua")), None).unwrap();
+        let bundled = format_items(&items, &FormatConfig::default()).output;
+        assert!(!bundled.contains("&i"));
+        let run = |code: &str| {
+            let mut env = Uiua::with_native_sys();
+            env.load_str(code).unwrap();
+            env.take_stack()
+        };
+        assert_eq!(run(&main), run(&bundled));
+
+        // Files that leave no values or several values
+        fs::write(path("none.ua"), "X ← 1").unwrap();
+        fs::write(path("two.ua"), "X ← 5\nX +1X").unwrap();
+        let main = format!(
+            "1\n&i {none:?}\n&i {two:?}\n&i {two:?}\nT ← &i {two:?}\nN ← &i {none:?}\nT",
+            none = path("none.ua"),
+            two = path("two.ua")
+        );
+        fs::write(path("stacks.ua"), &main).unwrap();
+        let items = bundle(Path::new(&path("stacks.ua")), None).unwrap();
+        let bundled = format_items(&items, &FormatConfig::default()).output;
+        assert_eq!(run(&main), run(&bundled));
+
+        fs::write(path("util.ua"), format!("&i {:?}", path("lib.ua"))).unwrap();
+        let err = bundle(Path::new(&path("main.ua")), None).unwrap_err();
+        assert!(err.to_string().contains("Cycle detected"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
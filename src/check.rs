@@ -38,6 +38,12 @@ struct VirtualEnv<'a> {
     min_height: usize,
 }
 
+/// The longest constant list whose items are tracked
+///
+/// Only short lists, like the arguments to `level` or `restack`, need their items,
+/// and tracking the items of large constants would make checking slower than running.
+const MAX_CHECKED_LIST: usize = 1000;
+
 #[derive(Debug, Clone)]
 enum BasicValue<'a> {
     Func(Cow<'a, Function>),
@@ -75,10 +81,13 @@ impl<'a> BasicValue<'a> {
             BasicValue::Num(*n)
         } else if let Some(n) = value.as_byte_array().and_then(Array::as_scalar) {
             BasicValue::Num(*n as f64)
-        } else if value.rank() == 1 {
+        } else if value.rank() == 1 && value.row_count() <= MAX_CHECKED_LIST {
             BasicValue::Arr(match value {
                 Value::Num(n) => n.data.iter().map(|n| BasicValue::Num(*n)).collect(),
                 Value::Byte(b) => b.data.iter().map(|b| BasicValue::Num(*b as f64)).collect(),
+                Value::Bits(b) => (0..b.flat_len())
+                    .map(|i| BasicValue::Num(b.get(i) as u8 as f64))
+                    .collect(),
                 Value::Char(c) => c.data.iter().map(|_| BasicValue::Other).collect(),
                 Value::Func(f) => f
                     .data
@@ -120,6 +129,7 @@ impl<'a> VirtualEnv<'a> {
             }
             Instr::Dynamic(f) => self.handle_sig(f.signature)?,
             Instr::DropTemp { .. } => {}
+            Instr::Fused(_, instrs) => self.instrs(instrs)?,
             Instr::Prim(prim, _) => match prim {
                 Reduce | Scan => {
                     let f = self.pop()?;
@@ -513,7 +523,7 @@ impl<'a> VirtualEnv<'a> {
                         _ => self.stack.push(BasicValue::Other),
                     }
                 }
-                Call => self.handle_call()?,
+                Call | Memo => self.handle_call()?,
                 Recur => return Err("recur present".into()),
                 prim => {
                     let array_args = prim
@@ -545,7 +555,7 @@ impl<'a> VirtualEnv<'a> {
     /// At the end of checking, the minimum stack height is a component in calculating the signature.
     fn set_min_height(&mut self) {
         self.min_height = self.min_height.min(self.stack.len());
-        if let Some(h) = self.array_stack.last_mut() {
+        for h in &mut self.array_stack {
             *h = (*h).min(self.stack.len());
         }
     }
@@ -2,23 +2,34 @@ use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
     sync::Arc,
+    time::Duration,
 };
 
 use crate::{
     algorithm::invert::under_instrs,
     array::Array,
     ast::*,
+    bench::{is_bench, Bench},
     check::instrs_signature,
     function::*,
     lex::{CodeSpan, Sp, Span},
+    optimize::optimize,
     primitive::Primitive,
     run::RunMode,
+    sys::import_source,
+    testing::{test_name, TestResult},
     value::Value,
     Diagnostic, DiagnosticKind, Ident, SysOp, UiuaError, UiuaResult,
 };
 
 use crate::Uiua;
 
+fn words_have_import(words: &[Sp<Word>]) -> bool {
+    words
+        .iter()
+        .any(|w| matches!(w.value, Word::Primitive(Primitive::Sys(SysOp::Import))))
+}
+
 impl Uiua {
     pub(crate) fn items(&mut self, items: Vec<Item>, in_test: bool) -> UiuaResult {
         for item in items {
@@ -27,12 +38,10 @@ impl Uiua {
         Ok(())
     }
     fn item(&mut self, item: Item, in_test: bool) -> UiuaResult {
-        fn words_have_import(words: &[Sp<Word>]) -> bool {
-            words
-                .iter()
-                .any(|w| matches!(w.value, Word::Primitive(Primitive::Sys(SysOp::Import))))
-        }
         match item {
+            Item::Scoped { items, test: true } if self.mode == RunMode::Test => {
+                self.test_scope(items)?;
+            }
             Item::Scoped { items, test } => {
                 let scope_stack = self.in_scope(true, |env| env.items(items, test))?;
                 self.stack.extend(scope_stack);
@@ -42,6 +51,15 @@ impl Uiua {
                     RunMode::Normal => !in_test,
                     RunMode::Test => in_test,
                     RunMode::All => true,
+                    RunMode::Check => {
+                        if words_have_import(&words) {
+                            let instrs = self.compile_words(words, true)?;
+                            self.exec_global_instrs(instrs)?;
+                        } else {
+                            self.compile_words(words, true)?;
+                        }
+                        return Ok(());
+                    }
                 };
                 if can_run || words_have_import(&words) {
                     let instrs = self.compile_words(words, true)?;
@@ -51,16 +69,76 @@ impl Uiua {
             Item::Binding(binding) => {
                 let can_run = match self.mode {
                     RunMode::Normal => !in_test,
-                    RunMode::All | RunMode::Test => true,
+                    RunMode::All | RunMode::Test | RunMode::Check => true,
                 };
                 if can_run || words_have_import(&binding.words) {
                     self.binding(binding)?;
                 }
             }
+            Item::Import(import) => self.import_item(import)?,
             Item::ExtraNewlines(_) => {}
         }
         Ok(())
     }
+    /// Infer how many values some items leave on the stack without running them
+    ///
+    /// The items are compiled in their own scope.
+    /// Returns `None` if the signature of a line cannot be inferred.
+    pub(crate) fn items_stack_height(&mut self, items: Vec<Item>) -> UiuaResult<Option<usize>> {
+        let mut height = Some(0usize);
+        self.in_scope(true, |env| {
+            for item in items {
+                match item {
+                    Item::Words(words) => {
+                        let instrs = env.compile_words(words, true)?;
+                        height = (height.zip(instrs_signature(&instrs).ok()))
+                            .map(|(height, sig)| height.saturating_sub(sig.args) + sig.outputs);
+                    }
+                    Item::Scoped { test: true, .. } | Item::ExtraNewlines(_) => {}
+                    item => env.item(item, false)?,
+                }
+            }
+            Ok(())
+        })?;
+        Ok(height)
+    }
+    /// Bind a name to a function that is only used for signature inference
+    pub(crate) fn bind_signature(&mut self, name: Ident, sig: Signature) {
+        let func = Function::new(FunctionId::Named(name.clone()), Vec::new(), sig);
+        let mut globals = self.globals.lock();
+        self.scope.names.insert(name, globals.len());
+        globals.push(func.into());
+    }
+    /// Run a test scope and record its result
+    fn test_scope(&mut self, items: Vec<Item>) -> UiuaResult {
+        let (name, span) = test_name(&items);
+        if (self.test_filter.as_ref()).is_some_and(|filter| !name.contains(filter.as_str())) {
+            return Ok(());
+        }
+        let inline_height = self.inline_stack.len();
+        let under_height = self.under_stack.len();
+        let start = instant::now();
+        let res = self.in_scope(true, |env| env.items(items, true));
+        let duration = Duration::from_secs_f64((instant::now() - start).max(0.0) / 1000.0);
+        let error = match res {
+            Ok(scope_stack) => {
+                self.stack.extend(scope_stack);
+                None
+            }
+            Err(e) => {
+                self.inline_stack.truncate(inline_height);
+                self.under_stack.truncate(under_height);
+                Some(e)
+            }
+        };
+        self.test_results.push(TestResult {
+            name,
+            span,
+            duration,
+            error,
+        });
+        Ok(())
+    }
     fn add_span(&mut self, span: impl Into<Span>) -> usize {
         let mut spans = self.spans.lock();
         let idx = spans.len();
@@ -68,7 +146,30 @@ impl Uiua {
         idx
     }
     fn binding(&mut self, binding: Binding) -> UiuaResult {
-        let instrs = self.compile_words(binding.words, true)?;
+        if let Some(coverage) = &self.coverage {
+            let span = match binding.words.last() {
+                Some(word) => binding.name.span.clone().merge(word.span.clone()),
+                None => binding.name.span.clone(),
+            };
+            (coverage.lock().bindings).push(span.sp(binding.name.value.clone()));
+        }
+        // In check mode, only constants and bindings that get things from modules are evaluated
+        let can_run_check = words_have_import(&binding.words)
+            || (binding.words.iter()).any(|w| matches!(w.value, Word::Primitive(Primitive::Use)));
+        let bench = self.benches.is_some() && is_bench(&binding.name.value);
+        // Benchmarks measure running their code, so it is not evaluated while compiling
+        let fold = self.fold;
+        self.fold = fold && !bench;
+        let instrs = self.compile_words(binding.words, true);
+        self.fold = fold;
+        let instrs = instrs?;
+        if let Some(benches) = self.benches.as_mut().filter(|_| bench) {
+            benches.push(Bench {
+                name: binding.name.value.clone(),
+                span: binding.name.span.clone(),
+                instrs: instrs.clone(),
+            });
+        }
         let make_fn = |instrs: Vec<Instr>, sig: Signature| {
             let func = Function::new(FunctionId::Named(binding.name.value.clone()), instrs, sig);
             Value::from(func)
@@ -89,7 +190,10 @@ impl Uiua {
                     }
                 }
 
-                if sig.args == 0 {
+                let can_run = self.mode != RunMode::Check
+                    || can_run_check
+                    || matches!(instrs.as_slice(), [Instr::Push(_)]);
+                if sig.args == 0 && can_run {
                     self.exec_global_instrs(instrs)?;
                     if let Some(value) = self.stack.pop() {
                         match value {
@@ -130,9 +234,38 @@ impl Uiua {
         let mut globals = self.globals.lock();
         let idx = globals.len();
         globals.push(val);
+        if binding.private {
+            self.scope.exports.remove(&binding.name.value);
+        } else {
+            (self.scope.exports).insert(binding.name.value.clone(), idx);
+        }
         self.scope.names.insert(binding.name.value, idx);
         Ok(())
     }
+    fn import_item(&mut self, import: Import) -> UiuaResult {
+        let path = &import.path.value;
+        let resolved = self.resolve_import(import.path.span.path.as_deref(), path);
+        let input = import_source(self, &resolved.to_string_lossy())
+            .map_err(|e| UiuaError::Run(Span::Code(import.path.span.clone()).sp(e)))?;
+        let exports = self.load_module(&input, &resolved)?;
+        if let Some(name) = &import.name {
+            for (export, idx) in &exports {
+                let qualified: Ident = format!("{}~{export}", name.value).into();
+                self.scope.names.insert(qualified, *idx);
+            }
+            self.scope.modules.insert(name.value.clone());
+        }
+        for item in import.items {
+            let Some(idx) = exports.get(&item.value) else {
+                return Err(item
+                    .span
+                    .sp(format!("`{path}` does not export `{}`", item.value))
+                    .into());
+            };
+            self.scope.names.insert(item.value, *idx);
+        }
+        Ok(())
+    }
     fn compile_words(&mut self, words: Vec<Sp<Word>>, call: bool) -> UiuaResult<Vec<Instr>> {
         self.new_functions.push(Vec::new());
         self.words(words, call)?;
@@ -141,8 +274,22 @@ impl Uiua {
                 eprintln!("{}", diagnostic.show(true));
             }
         }
-        let instrs = self.new_functions.pop().unwrap();
-        Ok(instrs)
+        Ok(self.finish_function())
+    }
+    /// Take the instructions of the function being compiled, optimizing them
+    fn finish_function(&mut self) -> Vec<Instr> {
+        let mut instrs = self.new_functions.pop().unwrap();
+        if self.optimize {
+            instrs = optimize(instrs, self);
+        }
+        self.instrument(&instrs);
+        instrs
+    }
+    /// Record the spans of compiled instructions if coverage is being tracked
+    fn instrument(&self, instrs: &[Instr]) {
+        if let Some(coverage) = &self.coverage {
+            (coverage.lock().instrumented).extend(instrs.iter().filter_map(Instr::span));
+        }
     }
     fn compile_operand_words(
         &mut self,
@@ -172,19 +319,12 @@ impl Uiua {
     }
     /// Push an instruction to the current function being compiled
     ///
-    /// Also performs some optimizations if the instruction and the previous
-    /// instruction form some known pattern
+    /// Also checks for some patterns that are better written another way.
+    /// Optimizations are done once the whole function is compiled.
     fn push_instr(&mut self, instr: Instr) {
         use Primitive::*;
         let instrs = self.new_functions.last_mut().unwrap();
-        // Optimizations
         match (instrs.as_mut_slice(), instr) {
-            // Cosine
-            ([.., Instr::Prim(Eta, _), Instr::Prim(Add, _)], Instr::Prim(Sin, span)) => {
-                instrs.pop();
-                instrs.pop();
-                instrs.push(Instr::Prim(Cos, span));
-            }
             ([.., Instr::Prim(Over, a)], Instr::Prim(Flip, b)) => {
                 let a = *a;
                 instrs.push(Instr::Prim(Flip, b));
@@ -195,8 +335,6 @@ impl Uiua {
                     DiagnosticKind::Style,
                 ));
             }
-            // First reverse = last
-            ([.., Instr::Prim(top @ Reverse, _)], Instr::Prim(First, _)) => *top = Last,
             (_, instr) => instrs.push(instr),
         }
     }
@@ -342,7 +480,7 @@ impl Uiua {
                         constant: arr.constant,
                     });
                     if !call {
-                        let instrs = self.new_functions.pop().unwrap();
+                        let instrs = self.finish_function();
                         let sig =
                             instrs_signature(&instrs).unwrap_or_else(|_| Signature::new(0, 0));
                         let func = Function::new(FunctionId::Anonymous(word.span), instrs, sig);
@@ -357,22 +495,41 @@ impl Uiua {
         }
         Ok(())
     }
+    /// Get the global index of a name in scope
+    fn lookup(&self, ident: &Ident) -> Option<usize> {
+        self.scope
+            .names
+            .get(ident)
+            .or_else(|| {
+                self.higher_scopes
+                    .last()
+                    .filter(|_| self.scope.local)?
+                    .names
+                    .get(ident)
+            })
+            .copied()
+    }
     fn ident(&mut self, ident: Ident, span: CodeSpan, call: bool) -> UiuaResult {
-        if let Some(idx) = self.scope.names.get(&ident).or_else(|| {
-            self.higher_scopes
-                .last()
-                .filter(|_| self.scope.local)?
-                .names
-                .get(&ident)
-        }) {
+        if let Some(idx) = self.lookup(&ident) {
             // Name exists in scope
-            let value = self.globals.lock()[*idx].clone();
+            let value = self.globals.lock()[idx].clone();
             let should_call = matches!(&value, Value::Func(f) if f.shape.is_empty());
             self.push_instr(Instr::push(value));
             if should_call && call {
                 let span = self.add_span(span);
                 self.push_instr(Instr::Call(span));
             }
+        } else if let Some((module, name)) = ident.split_once('~') {
+            let is_module = self.scope.modules.contains(module)
+                || (self.higher_scopes.last())
+                    .filter(|_| self.scope.local)
+                    .is_some_and(|scope| scope.modules.contains(module));
+            return Err(if is_module {
+                span.sp(format!("Module `{module}` has no public binding `{name}`"))
+            } else {
+                span.sp(format!("Unknown module `{module}`"))
+            }
+            .into());
         } else {
             return Err(span.sp(format!("Unknown identifier `{ident}`")).into());
         }
@@ -639,7 +796,7 @@ impl Uiua {
                 modified.modifier.span.clone(),
                 true,
             )?;
-            let instrs = self.new_functions.pop().unwrap();
+            let instrs = self.finish_function();
             match instrs_signature(&instrs) {
                 Ok(sig) => {
                     let func =
@@ -0,0 +1,129 @@
+//! Tracking which code is executed
+//!
+//! Coverage is enabled with [`Uiua::with_coverage`](crate::Uiua::with_coverage).
+//! Every compiled span is recorded as instrumented, and every span whose
+//! instruction runs is counted.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    path::PathBuf,
+};
+
+use crate::{
+    lex::{CodeSpan, Sp, Span},
+    Ident,
+};
+
+/// Raw coverage data collected while running
+#[derive(Debug, Default)]
+pub(crate) struct CoverageData {
+    /// The number of times each span was executed
+    pub hits: HashMap<usize, u64>,
+    /// The spans of compiled instructions
+    pub instrumented: HashSet<usize>,
+    /// The names of bindings and the spans of their definitions
+    pub bindings: Vec<Sp<Ident>>,
+}
+
+/// Coverage of a single binding
+#[derive(Debug, Clone)]
+pub struct BindingCoverage {
+    pub name: Ident,
+    pub file: PathBuf,
+    /// The line the binding is defined on
+    pub line: usize,
+    /// The number of the binding's spans that were executed
+    pub covered: usize,
+    /// The number of spans in the binding
+    pub total: usize,
+    /// The most times any of the binding's spans were executed
+    pub hits: u64,
+}
+
+/// A summary of which lines of which files were executed
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// For each file, the number of times each instrumented line was executed
+    pub files: BTreeMap<PathBuf, BTreeMap<usize, u64>>,
+    /// Coverage of each binding, in the order they were defined
+    pub bindings: Vec<BindingCoverage>,
+}
+
+impl CoverageReport {
+    pub(crate) fn new(data: &CoverageData, spans: &[Span]) -> Self {
+        let mut files: BTreeMap<PathBuf, BTreeMap<usize, u64>> = BTreeMap::new();
+        let mut code_spans: Vec<(&CodeSpan, u64)> = Vec::new();
+        for &i in &data.instrumented {
+            let Some(Span::Code(span)) = spans.get(i) else {
+                continue;
+            };
+            let Some(path) = &span.path else {
+                continue;
+            };
+            let hits = data.hits.get(&i).copied().unwrap_or(0);
+            let line = (files.entry(path.to_path_buf()).or_default())
+                .entry(span.start.line)
+                .or_default();
+            *line = (*line).max(hits);
+            code_spans.push((span, hits));
+        }
+        let mut bindings = Vec::new();
+        for binding in &data.bindings {
+            let Some(path) = &binding.span.path else {
+                continue;
+            };
+            let mut coverage = BindingCoverage {
+                name: binding.value.clone(),
+                file: path.to_path_buf(),
+                line: binding.span.start.line,
+                covered: 0,
+                total: 0,
+                hits: 0,
+            };
+            for (span, hits) in &code_spans {
+                let inside = span.path.as_ref() == Some(path)
+                    && span.start >= binding.span.start
+                    && span.end <= binding.span.end;
+                if inside {
+                    coverage.total += 1;
+                    coverage.covered += (*hits > 0) as usize;
+                    coverage.hits = coverage.hits.max(*hits);
+                }
+            }
+            bindings.push(coverage);
+        }
+        CoverageReport { files, bindings }
+    }
+    /// The number of instrumented lines and the number of those that were executed
+    pub fn line_counts(&self) -> (usize, usize) {
+        let lines = self.files.values().flat_map(|lines| lines.values());
+        lines.fold((0, 0), |(found, hit), &hits| {
+            (found + 1, hit + (hits > 0) as usize)
+        })
+    }
+    /// Generate an lcov tracefile
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (path, lines) in &self.files {
+            _ = writeln!(lcov, "TN:\nSF:{}", path.display());
+            let bindings: Vec<&BindingCoverage> = (self.bindings.iter())
+                .filter(|binding| &binding.file == path)
+                .collect();
+            for binding in &bindings {
+                _ = writeln!(lcov, "FN:{},{}", binding.line, binding.name);
+            }
+            for binding in &bindings {
+                _ = writeln!(lcov, "FNDA:{},{}", binding.hits, binding.name);
+            }
+            let functions_hit = bindings.iter().filter(|b| b.hits > 0).count();
+            _ = writeln!(lcov, "FNF:{}\nFNH:{functions_hit}", bindings.len());
+            for (line, hits) in lines {
+                _ = writeln!(lcov, "DA:{line},{hits}");
+            }
+            let lines_hit = lines.values().filter(|&&hits| hits > 0).count();
+            _ = writeln!(lcov, "LF:{}\nLH:{lines_hit}\nend_of_record", lines.len());
+        }
+        lcov
+    }
+}
@@ -4,7 +4,11 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
     iter::{Skip, Take},
+    marker::PhantomData,
+    mem::{align_of, size_of},
     ops::{Bound, Deref, DerefMut, RangeBounds},
+    slice,
+    sync::Arc,
 };
 
 macro_rules! cowslice {
@@ -18,19 +22,83 @@ macro_rules! cowslice {
 
 pub(crate) use cowslice;
 use ecow::EcoVec;
+use memmap2::Mmap;
 
 pub struct CowSlice<T> {
-    data: EcoVec<T>,
-    start: u32,
-    end: u32,
+    data: Data<T>,
+    start: usize,
+    end: usize,
 }
 
+/// The storage behind a [`CowSlice`]
+enum Data<T> {
+    Owned(EcoVec<T>),
+    /// A read-only memory-mapped file
+    ///
+    /// This is copied into an owned vector the first time it is modified.
+    Mapped(Arc<Mmap>, PhantomData<T>),
+}
+
+impl<T: Clone> Clone for Data<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Data::Owned(vec) => Data::Owned(vec.clone()),
+            Data::Mapped(map, _) => Data::Mapped(map.clone(), PhantomData),
+        }
+    }
+}
+
+/// Element types that can be read directly from the bytes of a file
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of the type,
+/// and its alignment must be no greater than that of a memory page.
+pub unsafe trait Mappable: Copy {}
+
+unsafe impl Mappable for u8 {}
+unsafe impl Mappable for f64 {}
+
 impl<T> CowSlice<T> {
     pub fn new() -> Self {
         Self::default()
     }
     pub fn truncate(&mut self, len: usize) {
-        self.end = (self.start + len as u32).min(self.end);
+        self.end = self.start.saturating_add(len).min(self.end);
+    }
+    /// Get the underlying vector if it is owned, unshared, and fully in the slice
+    fn whole_unique_mut(&mut self) -> Option<&mut EcoVec<T>> {
+        if let Data::Owned(vec) = &mut self.data {
+            if vec.is_unique() && self.start == 0 && self.end == vec.len() {
+                return Some(vec);
+            }
+        }
+        None
+    }
+}
+
+impl<T: Mappable> CowSlice<T> {
+    /// Create a slice of the contents of a memory-mapped file
+    ///
+    /// Returns `None` if the length of the file is not a multiple of the element size.
+    pub fn from_map(map: Mmap) -> Option<Self> {
+        let extra = map.len() % size_of::<T>();
+        if extra > 0 {
+            return None;
+        }
+        if map.is_empty() {
+            return Some(Self::new());
+        }
+        assert_eq!(
+            map.as_ptr() as usize % align_of::<T>(),
+            0,
+            "memory maps are page-aligned"
+        );
+        Some(Self {
+            start: 0,
+            end: map.len() / size_of::<T>(),
+            data: Data::Mapped(Arc::new(map), PhantomData),
+        })
     }
 }
 
@@ -40,18 +108,7 @@ impl<T: Clone> CowSlice<T> {
     where
         R: RangeBounds<usize>,
     {
-        let start = match range.start_bound() {
-            Bound::Included(&start) => self.start + start as u32,
-            Bound::Excluded(&start) => self.start + start as u32 + 1,
-            Bound::Unbounded => self.start,
-        };
-        let end = match range.end_bound() {
-            Bound::Included(&end) => self.start + end as u32 + 1,
-            Bound::Excluded(&end) => self.start + end as u32,
-            Bound::Unbounded => self.end,
-        };
-        assert!(start <= end);
-        assert!(end <= self.end);
+        let (start, end) = slice_bounds(self.start, self.end, range);
         Self {
             data: self.data.clone(),
             start,
@@ -62,9 +119,9 @@ impl<T: Clone> CowSlice<T> {
     where
         F: FnOnce(&mut EcoVec<T>) -> R,
     {
-        if self.data.is_unique() && self.start == 0 && self.end == self.data.len() as u32 {
-            let res = f(&mut self.data);
-            self.end = self.data.len() as u32;
+        if let Some(vec) = self.whole_unique_mut() {
+            let res = f(vec);
+            self.end = vec.len();
             res
         } else {
             let mut vec = EcoVec::from(&**self);
@@ -75,6 +132,39 @@ impl<T: Clone> CowSlice<T> {
     }
 }
 
+/// Get the bounds in the underlying data of a range of a slice
+///
+/// Panics if the range is not within the slice.
+#[track_caller]
+fn slice_bounds(start: usize, end: usize, range: impl RangeBounds<usize>) -> (usize, usize) {
+    let offset = |i: usize| start.checked_add(i).expect("slice index overflow");
+    let new_start = match range.start_bound() {
+        Bound::Included(&i) => offset(i),
+        Bound::Excluded(&i) => offset(i) + 1,
+        Bound::Unbounded => start,
+    };
+    let new_end = match range.end_bound() {
+        Bound::Included(&i) => offset(i) + 1,
+        Bound::Excluded(&i) => offset(i),
+        Bound::Unbounded => end,
+    };
+    assert!(new_start <= new_end);
+    assert!(new_end <= end);
+    (new_start, new_end)
+}
+
+#[test]
+fn cow_slice_bounds_past_u32() {
+    let big = 1 << 33;
+    assert_eq!(slice_bounds(0, big, 1 << 32..), (1 << 32, big));
+    assert_eq!(
+        slice_bounds(1 << 32, big, 5..=9),
+        ((1 << 32) + 5, (1 << 32) + 10)
+    );
+    assert_eq!(slice_bounds(big - 3, big, ..2), (big - 3, big - 1));
+    assert!(std::panic::catch_unwind(|| slice_bounds(1 << 32, big, ..(1 << 32) + 1)).is_err());
+}
+
 #[test]
 fn cow_slice_modify() {
     let mut slice = CowSlice::from([1, 2, 3]);
@@ -90,7 +180,7 @@ fn cow_slice_modify() {
 impl<T> Default for CowSlice<T> {
     fn default() -> Self {
         Self {
-            data: EcoVec::new(),
+            data: Data::Owned(EcoVec::new()),
             start: 0,
             end: 0,
         }
@@ -110,16 +200,34 @@ impl<T: Clone> Clone for CowSlice<T> {
 impl<T> Deref for CowSlice<T> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
-        &self.data[self.start as usize..self.end as usize]
+        match &self.data {
+            Data::Owned(vec) => &vec[self.start..self.end],
+            // SAFETY: Mapped data is only made by `from_map`, which checks that
+            // the map is aligned and holds a whole number of `T`s, and
+            // `Mappable` guarantees that any bytes are a valid `T`.
+            // The bounds never grow past the end of the map.
+            Data::Mapped(map, _) => unsafe {
+                let ptr = (map.as_ptr() as *const T).add(self.start);
+                slice::from_raw_parts(ptr, self.end - self.start)
+            },
+        }
     }
 }
 
 impl<T: Clone> DerefMut for CowSlice<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        if !self.data.is_unique() {
+        let unique = match &mut self.data {
+            Data::Owned(vec) => vec.is_unique(),
+            Data::Mapped(..) => false,
+        };
+        if !unique {
             *self = self.to_vec().into();
         }
-        self.data.make_mut()
+        let (start, end) = (self.start, self.end);
+        let Data::Owned(vec) = &mut self.data else {
+            unreachable!("data was just made owned")
+        };
+        &mut vec.make_mut()[start..end]
     }
 }
 
@@ -133,22 +241,46 @@ fn cow_slice_deref_mut() {
     sub[1] = 5;
     assert_eq!(slice, [1, 7, 3, 4]);
     assert_eq!(sub, [7, 5]);
+
+    let mut sub = CowSlice::from([1, 2, 3, 4]).slice(2..);
+    sub[0] = 8;
+    assert_eq!(sub, [8, 4]);
+}
+
+#[test]
+fn cow_slice_mapped() {
+    let map = |bytes: &[u8]| {
+        let mut map = memmap2::MmapMut::map_anon(bytes.len()).unwrap();
+        map.copy_from_slice(bytes);
+        map.make_read_only().unwrap()
+    };
+    let slice = CowSlice::<u8>::from_map(map(&[1, 2, 3, 4])).unwrap();
+    let mut sub = slice.slice(1..);
+    assert_eq!(sub, [2, 3, 4]);
+    sub[0] = 7;
+    assert_eq!(slice, [1, 2, 3, 4]);
+    assert_eq!(sub, [7, 3, 4]);
+    assert_eq!(slice.into_iter().collect::<Vec<_>>(), [1, 2, 3, 4]);
+
+    let nums = CowSlice::<f64>::from_map(map(&2.5f64.to_ne_bytes())).unwrap();
+    assert_eq!(nums, [2.5]);
+    assert!(CowSlice::<f64>::from_map(map(&[0; 5])).is_none());
 }
 
 impl<T: Clone> From<Vec<T>> for CowSlice<T> {
     fn from(vec: Vec<T>) -> Self {
         Self {
             start: 0,
-            end: vec.len() as u32,
-            data: vec.into(),
+            end: vec.len(),
+            data: Data::Owned(vec.into()),
         }
     }
 }
 
 impl<T: Clone> From<CowSlice<T>> for Vec<T> {
     fn from(mut slice: CowSlice<T>) -> Self {
-        if slice.data.is_unique() && slice.start == 0 && slice.end == slice.data.len() as u32 {
-            slice.data.into_iter().collect()
+        if let Some(vec) = slice.whole_unique_mut() {
+            std::mem::take(vec).into_iter().collect()
         } else {
             slice.to_vec()
         }
@@ -159,8 +291,8 @@ impl<T: Clone> From<EcoVec<T>> for CowSlice<T> {
     fn from(data: EcoVec<T>) -> Self {
         Self {
             start: 0,
-            end: data.len() as u32,
-            data,
+            end: data.len(),
+            data: Data::Owned(data),
         }
     }
 }
@@ -169,8 +301,8 @@ impl<'a, T: Clone> From<&'a [T]> for CowSlice<T> {
     fn from(slice: &'a [T]) -> Self {
         Self {
             start: 0,
-            end: slice.len() as u32,
-            data: slice.into(),
+            end: slice.len(),
+            data: Data::Owned(slice.into()),
         }
     }
 }
@@ -179,8 +311,8 @@ impl<T: Clone, const N: usize> From<[T; N]> for CowSlice<T> {
     fn from(array: [T; N]) -> Self {
         Self {
             start: 0,
-            end: N as u32,
-            data: array.into(),
+            end: N,
+            data: Data::Owned(array.into()),
         }
     }
 }
@@ -252,10 +384,12 @@ impl<T: Clone> IntoIterator for CowSlice<T> {
     type IntoIter = Take<Skip<<EcoVec<T> as IntoIterator>::IntoIter>>;
     #[allow(clippy::unnecessary_to_owned)]
     fn into_iter(self) -> Self::IntoIter {
-        self.data
-            .into_iter()
-            .skip(self.start as usize)
-            .take((self.end - self.start) as usize)
+        let len = self.end - self.start;
+        let (vec, start) = match self.data {
+            Data::Owned(vec) => (vec, self.start),
+            Data::Mapped(..) => (EcoVec::from(&*self), 0),
+        };
+        vec.into_iter().skip(start).take(len)
     }
 }
 
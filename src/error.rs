@@ -8,11 +8,12 @@ use std::{
 };
 
 use ariadne::{Color, Config, Label, Report, ReportKind, Source};
+use serde::Serialize;
 
 use crate::{
     example_ua,
     function::FunctionId,
-    lex::{CodeSpan, Sp, Span},
+    lex::{CodeSpan, Loc, Sp, Span},
     parse::ParseError,
     value::Value,
 };
@@ -219,6 +220,154 @@ impl UiuaError {
     }
 }
 
+impl UiuaError {
+    /// Get structured records of the error, for tools that consume Uiua's output
+    ///
+    /// Most errors produce a single record, but there is one per error for parse errors.
+    pub fn records(&self) -> Vec<Record> {
+        match self {
+            UiuaError::Load(path, _) | UiuaError::Format(path, _) => vec![Record {
+                kind: RecordKind::Error,
+                message: self.to_string(),
+                file: Some(path.clone()),
+                span: None,
+                trace: Vec::new(),
+            }],
+            UiuaError::Parse(errors) => errors
+                .iter()
+                .map(|error| {
+                    Record::new(RecordKind::Error, &error.value, &error.span.clone().into())
+                })
+                .collect(),
+            UiuaError::Run(error) => {
+                vec![Record::new(RecordKind::Error, &error.value, &error.span)]
+            }
+            UiuaError::Traced { error, trace } => {
+                let frames: Vec<RecordFrame> = (trace.iter())
+                    .filter(|frame| frame.id != FunctionId::Main)
+                    .map(|frame| {
+                        let (file, span) = RecordSpan::from_span(&frame.span);
+                        RecordFrame {
+                            function: frame.id.to_string(),
+                            file,
+                            span,
+                        }
+                    })
+                    .collect();
+                let mut records = error.records();
+                for record in &mut records {
+                    record.trace.extend(frames.iter().cloned());
+                }
+                records
+            }
+            UiuaError::Throw(value, span) => vec![Record::new(RecordKind::Error, value, span)],
+            UiuaError::Break(_, span) => {
+                vec![Record::new(
+                    RecordKind::Error,
+                    "break outside of loop",
+                    span,
+                )]
+            }
+            UiuaError::Timeout(span) => vec![Record::new(
+                RecordKind::Error,
+                "Maximum execution time exceeded",
+                span,
+            )],
+            UiuaError::Fill(error) => error.records(),
+        }
+    }
+}
+
+/// A structured error or diagnostic
+///
+/// This is what `--message-format json` emits, one record per line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Record {
+    pub kind: RecordKind,
+    pub message: String,
+    /// The file the record refers to, if any
+    pub file: Option<PathBuf>,
+    /// The location in the file, if any
+    pub span: Option<RecordSpan>,
+    /// The functions that were being called when an error occurred, innermost first
+    pub trace: Vec<RecordFrame>,
+}
+
+/// The kind of a [`Record`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordKind {
+    Error,
+    Warning,
+    Advice,
+    Style,
+}
+
+/// A range of lines and columns in a [`Record`]
+///
+/// Lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RecordSpan {
+    pub start: RecordLoc,
+    pub end: RecordLoc,
+}
+
+/// A line and column in a [`RecordSpan`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RecordLoc {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// A function call in a [`Record`]'s trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordFrame {
+    pub function: String,
+    pub file: Option<PathBuf>,
+    pub span: Option<RecordSpan>,
+}
+
+impl Record {
+    fn new(kind: RecordKind, message: impl ToString, span: &Span) -> Self {
+        let (file, span) = RecordSpan::from_span(span);
+        Record {
+            kind,
+            message: message.to_string(),
+            file,
+            span,
+            trace: Vec::new(),
+        }
+    }
+    /// Serialize the record as a single line of JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("record should serialize")
+    }
+}
+
+impl RecordSpan {
+    fn from_span(span: &Span) -> (Option<PathBuf>, Option<Self>) {
+        match span {
+            Span::Code(span) => (
+                span.path.as_deref().map(Path::to_path_buf),
+                Some(RecordSpan {
+                    start: span.start.into(),
+                    end: span.end.into(),
+                }),
+            ),
+            Span::Builtin => (None, None),
+        }
+    }
+}
+
+impl From<Loc> for RecordLoc {
+    fn from(loc: Loc) -> Self {
+        RecordLoc {
+            line: loc.line,
+            col: loc.col,
+        }
+    }
+}
+
 /// A message to be displayed to the user that is not an error
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Diagnostic {
@@ -248,6 +397,15 @@ impl Diagnostic {
             kind,
         }
     }
+    /// Get a structured record of the diagnostic
+    pub fn record(&self) -> Record {
+        let kind = match self.kind {
+            DiagnosticKind::Warning => RecordKind::Warning,
+            DiagnosticKind::Advice => RecordKind::Advice,
+            DiagnosticKind::Style => RecordKind::Style,
+        };
+        Record::new(kind, &self.message, &self.span)
+    }
     pub fn show(&self, color: bool) -> String {
         report(
             [(&self.message, self.span.clone())],
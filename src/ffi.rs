@@ -0,0 +1,460 @@
+//! Calling functions in C shared libraries
+
+use std::{mem::size_of, str::FromStr};
+
+/// A numeric C type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfiScalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+/// A C type that can be passed to or returned from a foreign function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FfiType {
+    /// No value, only valid as a return type
+    Void,
+    /// A number
+    Scalar(FfiScalar),
+    /// A nul-terminated string
+    CStr,
+    /// A pointer to a buffer of numbers
+    ///
+    /// Buffers behind non-`const` pointers are returned after the call
+    Ptr { scalar: FfiScalar, mutable: bool },
+}
+
+impl FfiScalar {
+    fn from_c_name(name: &str) -> Option<Self> {
+        use FfiScalar::*;
+        let int = |size: usize, signed: bool| match (size, signed) {
+            (4, true) => I32,
+            (4, false) => U32,
+            (_, true) => I64,
+            (_, false) => U64,
+        };
+        Some(match name {
+            "char" | "signed char" | "int8_t" => I8,
+            "unsigned char" | "uint8_t" | "bool" | "_Bool" => U8,
+            "short" | "int16_t" => I16,
+            "unsigned short" | "uint16_t" => U16,
+            "int" | "int32_t" => I32,
+            "unsigned int" | "unsigned" | "uint32_t" => U32,
+            "long" => int(size_of::<std::ffi::c_long>(), true),
+            "unsigned long" => int(size_of::<std::ffi::c_ulong>(), false),
+            "long long" | "int64_t" => I64,
+            "unsigned long long" | "uint64_t" => U64,
+            "size_t" => int(size_of::<usize>(), false),
+            "float" => F32,
+            "double" => F64,
+            _ => return None,
+        })
+    }
+    /// The size of the type in bytes
+    pub fn size(self) -> usize {
+        use FfiScalar::*;
+        match self {
+            I8 | U8 => 1,
+            I16 | U16 => 2,
+            I32 | U32 | F32 => 4,
+            I64 | U64 | F64 => 8,
+        }
+    }
+}
+
+impl FromStr for FfiType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name == "void" {
+            return Ok(FfiType::Void);
+        }
+        let Some(pointee) = name.strip_suffix('*') else {
+            return FfiScalar::from_c_name(&name)
+                .map(FfiType::Scalar)
+                .ok_or_else(|| format!("Unknown or unsupported C type `{s}`"));
+        };
+        let pointee = pointee.trim_end();
+        let (pointee, mutable) = match pointee.strip_prefix("const ") {
+            Some(pointee) => (pointee, false),
+            None => (pointee, true),
+        };
+        if pointee == "char" {
+            return Ok(FfiType::CStr);
+        }
+        FfiScalar::from_c_name(pointee)
+            .map(|scalar| FfiType::Ptr { scalar, mutable })
+            .ok_or_else(|| format!("Unknown or unsupported C type `{s}`"))
+    }
+}
+
+#[cfg(feature = "ffi")]
+pub(crate) use native::ffi_call;
+
+#[cfg(feature = "ffi")]
+mod native {
+    use std::{
+        ffi::{c_char, c_void, CStr, CString},
+        sync::Arc,
+    };
+
+    use dashmap::DashMap;
+    use libffi::middle::{Arg, Cif, CodePtr, Type};
+    use libloading::Library;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+    use crate::{array::Shape, function::Function, value::Value};
+
+    impl FfiScalar {
+        fn ffi_type(self) -> Type {
+            use FfiScalar::*;
+            match self {
+                I8 => Type::i8(),
+                U8 => Type::u8(),
+                I16 => Type::i16(),
+                U16 => Type::u16(),
+                I32 => Type::i32(),
+                U32 => Type::u32(),
+                I64 => Type::i64(),
+                U64 => Type::u64(),
+                F32 => Type::f32(),
+                F64 => Type::f64(),
+            }
+        }
+        /// Write a number to memory as this type
+        ///
+        /// # Safety
+        /// `ptr` must be valid and aligned for a write of this type
+        unsafe fn write(self, ptr: *mut u8, n: f64) {
+            use FfiScalar::*;
+            match self {
+                I8 => (ptr as *mut i8).write(n as i8),
+                U8 => ptr.write(n as u8),
+                I16 => (ptr as *mut i16).write(n as i16),
+                U16 => (ptr as *mut u16).write(n as u16),
+                I32 => (ptr as *mut i32).write(n as i32),
+                U32 => (ptr as *mut u32).write(n as u32),
+                I64 => (ptr as *mut i64).write(n as i64),
+                U64 => (ptr as *mut u64).write(n as u64),
+                F32 => (ptr as *mut f32).write(n as f32),
+                F64 => (ptr as *mut f64).write(n),
+            }
+        }
+        /// Read a number of this type from memory
+        ///
+        /// # Safety
+        /// `ptr` must be valid and aligned for a read of this type
+        unsafe fn read(self, ptr: *const u8) -> f64 {
+            use FfiScalar::*;
+            match self {
+                I8 => (ptr as *const i8).read() as f64,
+                U8 => ptr.read() as f64,
+                I16 => (ptr as *const i16).read() as f64,
+                U16 => (ptr as *const u16).read() as f64,
+                I32 => (ptr as *const i32).read() as f64,
+                U32 => (ptr as *const u32).read() as f64,
+                I64 => (ptr as *const i64).read() as f64,
+                U64 => (ptr as *const u64).read() as f64,
+                F32 => (ptr as *const f32).read() as f64,
+                F64 => (ptr as *const f64).read(),
+            }
+        }
+        /// Convert an integer return value, which libffi widens to a full register
+        fn int_return(self, r: u64) -> f64 {
+            use FfiScalar::*;
+            match self {
+                I8 => r as i8 as f64,
+                U8 => r as u8 as f64,
+                I16 => r as i16 as f64,
+                U16 => r as u16 as f64,
+                I32 => r as i32 as f64,
+                U32 => r as u32 as f64,
+                I64 => r as i64 as f64,
+                U64 => r as f64,
+                F32 | F64 => unreachable!("float returned as int"),
+            }
+        }
+    }
+
+    impl FfiType {
+        fn ffi_type(self) -> Type {
+            match self {
+                FfiType::Void => Type::void(),
+                FfiType::Scalar(scalar) => scalar.ffi_type(),
+                FfiType::CStr | FfiType::Ptr { .. } => Type::pointer(),
+            }
+        }
+    }
+
+    /// A buffer of numbers aligned for any scalar type
+    struct Buffer {
+        scalar: FfiScalar,
+        shape: Shape,
+        len: usize,
+        data: Vec<u64>,
+    }
+
+    impl Buffer {
+        fn new(scalar: FfiScalar, shape: Shape, nums: &[f64]) -> Self {
+            let bytes = scalar.size() * nums.len();
+            let mut data = vec![0u64; bytes.div_ceil(8).max(1)];
+            let ptr = data.as_mut_ptr() as *mut u8;
+            for (i, &n) in nums.iter().enumerate() {
+                // Safety: the buffer has room for every element and is 8-byte aligned
+                unsafe { scalar.write(ptr.add(i * scalar.size()), n) };
+            }
+            Buffer {
+                scalar,
+                shape,
+                len: nums.len(),
+                data,
+            }
+        }
+        fn into_value(self) -> Value {
+            let ptr = self.data.as_ptr() as *const u8;
+            let nums: Vec<f64> = (0..self.len)
+                // Safety: the buffer has room for every element and is 8-byte aligned
+                .map(|i| unsafe { self.scalar.read(ptr.add(i * self.scalar.size())) })
+                .collect();
+            if self.scalar == FfiScalar::U8 {
+                (
+                    self.shape,
+                    nums.into_iter().map(|n| n as u8).collect::<Vec<u8>>(),
+                )
+                    .into()
+            } else {
+                (self.shape, nums).into()
+            }
+        }
+    }
+
+    fn numbers(value: &Value) -> Option<(Shape, Vec<f64>)> {
+        match value {
+            Value::Num(arr) => Some((arr.shape.clone(), arr.data.to_vec())),
+            Value::Byte(arr) => Some((
+                arr.shape.clone(),
+                arr.data.iter().map(|&b| b as f64).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Call a function in a shared library
+    ///
+    /// If the function returns `void` and takes no mutable pointers, the result is an empty list.
+    /// If there is exactly one output, it is returned as is.
+    /// Otherwise, the outputs are returned as a list of boxes, starting with the return value.
+    pub(crate) fn ffi_call(
+        file: &str,
+        return_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        static LIBRARIES: Lazy<DashMap<String, Arc<Library>>> = Lazy::new(DashMap::new);
+
+        if let FfiType::Ptr { .. } = return_ty {
+            return Err("Foreign functions may only return numbers or strings".into());
+        }
+        if arg_tys.len() != args.len() {
+            return Err(format!(
+                "{name} takes {} argument(s), but {} were given",
+                arg_tys.len(),
+                args.len()
+            ));
+        }
+
+        // Marshal arguments
+        let mut slots = vec![0u64; args.len()];
+        let mut strings = Vec::new();
+        let mut buffers = Vec::new();
+        for (i, (ty, arg)) in arg_tys.iter().zip(args).enumerate() {
+            let slot = &mut slots[i] as *mut u64 as *mut u8;
+            match *ty {
+                FfiType::Void => return Err("Arguments cannot be void".into()),
+                FfiType::Scalar(scalar) => match numbers(arg) {
+                    Some((shape, nums)) if shape.is_empty() => {
+                        // Safety: every slot is 8-byte aligned and 8 bytes long
+                        unsafe { scalar.write(slot, nums[0]) }
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Argument {} of {name} must be a scalar number, but it is {}",
+                            i + 1,
+                            describe(arg)
+                        ))
+                    }
+                },
+                FfiType::CStr => {
+                    let Value::Char(arr) = arg else {
+                        return Err(format!(
+                            "Argument {} of {name} must be a string, but it is {}",
+                            i + 1,
+                            describe(arg)
+                        ));
+                    };
+                    let s = CString::new(arr.data.iter().collect::<String>())
+                        .map_err(|_| format!("Argument {} of {name} contains a nul", i + 1))?;
+                    // Safety: every slot is 8-byte aligned and 8 bytes long
+                    unsafe { (slot as *mut *const c_char).write(s.as_ptr()) };
+                    strings.push(s);
+                }
+                FfiType::Ptr { scalar, mutable } => {
+                    let Some((shape, nums)) = numbers(arg) else {
+                        return Err(format!(
+                            "Argument {} of {name} must be a numeric array, but it is {}",
+                            i + 1,
+                            describe(arg)
+                        ));
+                    };
+                    let mut buffer = Buffer::new(scalar, shape, &nums);
+                    // Safety: every slot is 8-byte aligned and 8 bytes long
+                    unsafe { (slot as *mut *mut c_void).write(buffer.data.as_mut_ptr() as _) };
+                    buffers.push((buffer, mutable));
+                }
+            }
+        }
+
+        // Look up the function
+        let lib = if let Some(lib) = LIBRARIES.get(file) {
+            lib.clone()
+        } else {
+            // Safety: loading a library runs its initializers, which is up to the caller
+            let lib = Arc::new(unsafe { Library::new(file) }.map_err(|e| e.to_string())?);
+            LIBRARIES.insert(file.into(), lib.clone());
+            lib
+        };
+        // Safety: the symbol is only used as an untyped code pointer
+        let fptr =
+            unsafe { lib.get::<*const c_void>(name.as_bytes()) }.map_err(|e| e.to_string())?;
+        let code = CodePtr::from_ptr(*fptr);
+
+        // Call it
+        let cif = Cif::new(arg_tys.iter().map(|ty| ty.ffi_type()), return_ty.ffi_type());
+        let ffi_args: Vec<Arg> = slots.iter().map(Arg::new).collect();
+        // Safety: the argument slots match the declared signature,
+        // and it is up to the caller to declare it correctly
+        let ret = unsafe {
+            match return_ty {
+                FfiType::Void => {
+                    cif.call::<()>(code, &ffi_args);
+                    None
+                }
+                FfiType::Scalar(FfiScalar::F32) => {
+                    Some(Value::from(cif.call::<f32>(code, &ffi_args) as f64))
+                }
+                FfiType::Scalar(FfiScalar::F64) => {
+                    Some(Value::from(cif.call::<f64>(code, &ffi_args)))
+                }
+                FfiType::Scalar(scalar) => Some(Value::from(
+                    scalar.int_return(cif.call::<u64>(code, &ffi_args)),
+                )),
+                FfiType::CStr => {
+                    let ptr = cif.call::<*const c_char>(code, &ffi_args);
+                    if ptr.is_null() {
+                        return Err(format!("{name} returned a null string"));
+                    }
+                    Some(Value::from(
+                        CStr::from_ptr(ptr).to_string_lossy().into_owned(),
+                    ))
+                }
+                FfiType::Ptr { .. } => unreachable!(),
+            }
+        };
+        drop(strings);
+
+        // Collect outputs
+        let mut outputs: Vec<Value> = ret.into_iter().collect();
+        for (buffer, mutable) in buffers {
+            if mutable {
+                outputs.push(buffer.into_value());
+            }
+        }
+        Ok(if outputs.len() == 1 {
+            outputs.pop().unwrap()
+        } else {
+            outputs
+                .into_iter()
+                .map(|val| Arc::new(Function::constant(val)))
+                .collect()
+        })
+    }
+
+    fn describe(value: &Value) -> String {
+        if value.rank() == 0 {
+            format!("a scalar {}", value.type_name())
+        } else {
+            format!("a rank {} {} array", value.rank(), value.type_name())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ffi", target_os = "linux"))]
+mod tests {
+    use std::{env, fs, process::Command};
+
+    use crate::Uiua;
+
+    const C_SOURCE: &str = r#"
+int add(int a, int b) { return a + b; }
+double mean(const double* xs, int len) {
+    double sum = 0;
+    for (int i = 0; i < len; i++) sum += xs[i];
+    return sum / len;
+}
+void double_all(int* xs, int len) {
+    for (int i = 0; i < len; i++) xs[i] *= 2;
+}
+int sum_and_double(int* xs, int len) {
+    int sum = 0;
+    for (int i = 0; i < len; i++) { sum += xs[i]; xs[i] *= 2; }
+    return sum;
+}
+signed char negate(signed char c) { return -c; }
+unsigned char first(const char* s) { return s[0]; }
+const char* greeting(void) { return "hello"; }
+"#;
+
+    #[test]
+    fn c_library() {
+        let dir = env::temp_dir().join(format!("uiua-ffi-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("lib.c");
+        let lib = dir.join("libtest.so");
+        fs::write(&src, C_SOURCE).unwrap();
+        let status = Command::new("cc")
+            .args(["-shared", "-fPIC", "-o"])
+            .arg(&lib)
+            .arg(&src)
+            .status()
+            .expect("failed to run cc");
+        assert!(status.success(), "failed to compile test library");
+
+        let lib = lib.display();
+        let code = format!(
+            r#"
+⍤.≅ 5 &ffi {{"{lib}" "int" "add" "int" "int"}} {{2 3}}
+⍤.≅ 2.5 &ffi {{"{lib}" "double" "mean" "const double*" "int"}} {{[1 2 3 4] 4}}
+⍤.≅ [2 4 6] &ffi {{"{lib}" "void" "double_all" "int*" "int"}} {{[1 2 3] 3}}
+⍤.≅ {{6 [2 4 6]}} &ffi {{"{lib}" "int" "sum_and_double" "int*" "int"}} {{[1 2 3] 3}}
+⍤.≅ ¯5 &ffi {{"{lib}" "signed char" "negate" "signed char"}} {{5}}
+⍤.≅ 97 &ffi {{"{lib}" "unsigned char" "first" "const char*"}} {{"abc"}}
+⍤.≅ "hello" &ffi {{"{lib}" "const char*" "greeting"}} []
+"#
+        );
+        let res = Uiua::with_native_sys().load_str(&code);
+        fs::remove_dir_all(&dir).ok();
+        if let Err(e) = res {
+            panic!("{}", e.show(true));
+        }
+    }
+}
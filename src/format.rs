@@ -21,8 +21,8 @@ use crate::{
     SysBackend, Uiua, UiuaError, UiuaResult,
 };
 
-// For now disallow any syscalls in the format config file.
-struct FormatConfigBackend;
+// For now disallow any syscalls in config files.
+pub(crate) struct FormatConfigBackend;
 
 impl SysBackend for FormatConfigBackend {
     fn any(&self) -> &dyn Any {
@@ -395,7 +395,6 @@ impl<'a> Formatter<'a> {
                     groups.push((line_len, vec![(line_number, comment)]));
                 }
             }
-            println!("{:#?}", groups);
             // Append comments to lines
             for (max, group) in groups {
                 for (line_number, comment) in group {
@@ -412,7 +411,11 @@ impl<'a> Formatter<'a> {
                     line.push_str(&comment);
                 }
             }
+            let trailing_newline = self.output.ends_with('\n');
             self.output = lines.join("\n");
+            if trailing_newline {
+                self.output.push('\n');
+            }
         }
     }
     fn format_item(&mut self, item: &Item) {
@@ -428,6 +431,9 @@ impl<'a> Formatter<'a> {
                 self.format_words(w, true, 0);
             }
             Item::Binding(binding) => {
+                if binding.private {
+                    self.output.push('~');
+                }
                 self.output.push_str(&binding.name.value);
                 self.output.push_str(" ←");
                 if !binding.words.is_empty() || binding.signature.is_some() {
@@ -438,6 +444,22 @@ impl<'a> Formatter<'a> {
                 }
                 self.format_words(&binding.words, true, 0);
             }
+            Item::Import(import) => {
+                if let Some(name) = &import.name {
+                    self.output.push_str(&name.value);
+                    self.output.push(' ');
+                }
+                self.output.push_str("~ ");
+                let path = import.path.clone().map(Word::String);
+                self.format_word(&path, 0);
+                if !import.items.is_empty() {
+                    self.output.push_str(" ~");
+                    for item in &import.items {
+                        self.output.push(' ');
+                        self.output.push_str(&item.value);
+                    }
+                }
+            }
             Item::ExtraNewlines(_) => {}
         }
     }
@@ -473,7 +495,7 @@ impl<'a> Formatter<'a> {
                 self.output.push('@');
                 self.output.push_str(formatted);
             }
-            Word::String(s) => self.output.push_str(&format!("{:?}", s)),
+            Word::String(s) => self.output.push_str(&format_string(s)),
             Word::FormatString(_) => self.output.push_str(word.span.as_str()),
             Word::MultilineString(lines) => {
                 if lines.len() == 1 {
@@ -548,13 +570,14 @@ impl<'a> Formatter<'a> {
             }
             Word::Spaces => self.push(&word.span, " "),
             Word::Comment(comment) => {
-                let beginning_of_line = self
-                    .output
-                    .lines()
-                    .last()
-                    .unwrap_or_default()
-                    .trim()
-                    .is_empty();
+                let beginning_of_line = self.output.ends_with('\n')
+                    || self
+                        .output
+                        .lines()
+                        .last()
+                        .unwrap_or_default()
+                        .trim()
+                        .is_empty();
                 if beginning_of_line || !self.config.align_comments {
                     self.output.push('#');
                     if !comment.starts_with(' ')
@@ -642,6 +665,25 @@ impl<'a> Formatter<'a> {
     }
 }
 
+/// Format a string literal using the escapes that the lexer understands
+fn format_string(s: &str) -> String {
+    let mut formatted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '\n' => formatted.push_str("\\n"),
+            '\r' => formatted.push_str("\\r"),
+            '\t' => formatted.push_str("\\t"),
+            '\0' => formatted.push_str("\\0"),
+            '\\' => formatted.push_str("\\\\"),
+            '"' => formatted.push_str("\\\""),
+            char::MAX => formatted.push_str("\\_"),
+            c => formatted.push(c),
+        }
+    }
+    formatted.push('"');
+    formatted
+}
+
 fn trim_spaces(words: &[Sp<Word>], trim_end: bool) -> &[Sp<Word>] {
     let mut start = 0;
     for word in words {
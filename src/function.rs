@@ -7,8 +7,8 @@ use std::{
 };
 
 use crate::{
-    check::instrs_signature, lex::CodeSpan, primitive::Primitive, value::Value, Ident, Uiua,
-    UiuaResult,
+    check::instrs_signature, lex::CodeSpan, optimize::Fused, primitive::Primitive, value::Value,
+    Ident, Uiua, UiuaResult,
 };
 
 #[derive(Clone)]
@@ -44,6 +44,8 @@ pub enum Instr {
         span: usize,
         kind: TempKind,
     },
+    /// An idiom run by a dedicated kernel, and the instructions it replaces
+    Fused(Fused, Arc<[Instr]>),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -75,6 +77,7 @@ impl PartialEq for Instr {
                 },
             ) => ao == bo && ac == bc,
             (Self::DropTemp { count: a, .. }, Self::DropTemp { count: b, .. }) => a == b,
+            (Self::Fused(a, a_instrs), Self::Fused(b, b_instrs)) => a == b && a_instrs == b_instrs,
             _ => false,
         }
     }
@@ -123,6 +126,10 @@ impl Hash for Instr {
                 count.hash(state);
             }
             Instr::DropTemp { count, .. } => count.hash(state),
+            Instr::Fused(fused, instrs) => {
+                fused.hash(state);
+                instrs.hash(state);
+            }
         }
     }
 }
@@ -131,6 +138,20 @@ impl Instr {
     pub fn push(val: impl Into<Value>) -> Self {
         Self::Push(Box::new(val.into()))
     }
+    /// Get the index of the instruction's span, if it has one
+    pub(crate) fn span(&self) -> Option<usize> {
+        match self {
+            Instr::Push(_) | Instr::BeginArray | Instr::Dynamic(_) => None,
+            Instr::EndArray { span, .. }
+            | Instr::Prim(_, span)
+            | Instr::Call(span)
+            | Instr::PushTemp { span, .. }
+            | Instr::PopTemp { span, .. }
+            | Instr::CopyTemp { span, .. }
+            | Instr::DropTemp { span, .. } => Some(*span),
+            Instr::Fused(_, instrs) => instrs.last().and_then(Instr::span),
+        }
+    }
     pub fn as_push(&self) -> Option<&Value> {
         match self {
             Instr::Push(val) => Some(val),
@@ -173,6 +194,7 @@ impl fmt::Display for Instr {
                 ..
             } => write!(f, "<copy {kind:?} {offset}/{count}>"),
             Instr::DropTemp { count, kind, .. } => write!(f, "<drop {kind:?} {count}>"),
+            Instr::Fused(fused, _) => write!(f, "{fused}"),
         }
     }
 }
@@ -404,6 +426,10 @@ impl Function {
             _ => None,
         }
     }
+    /// Check if calling the function has no side effects and does not depend on the order of calls
+    pub(crate) fn is_pure(&self) -> bool {
+        instrs_are_pure(&self.instrs)
+    }
     pub(crate) fn as_flipped_primitive(&self) -> Option<(Primitive, bool)> {
         match &self.id {
             FunctionId::Primitive(prim) => Some((*prim, false)),
@@ -423,6 +449,30 @@ impl Function {
     }
 }
 
+/// Check if running instructions has no side effects and does not depend on the order of runs
+fn instrs_are_pure(instrs: &[Instr]) -> bool {
+    instrs.iter().enumerate().all(|(i, instr)| match instr {
+        Instr::Push(val) => match &**val {
+            Value::Func(fs) => fs.data.iter().all(|f| f.is_pure()),
+            _ => true,
+        },
+        // Calls are only known to be pure if the function being called was just pushed,
+        // since it was checked above. Functions passed in as arguments could be anything.
+        Instr::Call(_) | Instr::Prim(Primitive::Call, _) => {
+            i > 0 && matches!(&instrs[i - 1], Instr::Push(val) if matches!(**val, Value::Func(_)))
+        }
+        Instr::Prim(prim, _) => prim.is_pure(),
+        Instr::Fused(_, instrs) => instrs_are_pure(instrs),
+        Instr::BeginArray
+        | Instr::EndArray { .. }
+        | Instr::PushTemp { .. }
+        | Instr::PopTemp { .. }
+        | Instr::CopyTemp { .. }
+        | Instr::DropTemp { .. } => true,
+        Instr::Dynamic(_) => false,
+    })
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum FunctionId {
     Named(Ident),
@@ -137,6 +137,7 @@ impl GridFmt for Value {
             Value::Byte(array) => array.fmt_grid(boxed),
             Value::Char(array) => array.fmt_grid(boxed),
             Value::Func(array) => array.fmt_grid(boxed),
+            Value::Bits(bits) => bits.to_bytes().fmt_grid(boxed),
         }
     }
 }
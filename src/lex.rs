@@ -343,6 +343,7 @@ pub enum AsciiToken {
     LessEqual,
     GreaterEqual,
     Backtick,
+    Tilde,
     TripleMinus,
     TripleTilde,
 }
@@ -367,6 +368,7 @@ impl fmt::Display for AsciiToken {
             AsciiToken::LessEqual => write!(f, "<="),
             AsciiToken::GreaterEqual => write!(f, ">="),
             AsciiToken::Backtick => write!(f, "`"),
+            AsciiToken::Tilde => write!(f, "~"),
             AsciiToken::TripleMinus => write!(f, "---"),
             AsciiToken::TripleTilde => write!(f, "~~~"),
         }
@@ -485,6 +487,7 @@ impl Lexer {
                 '_' => self.end(Underscore, start),
                 '|' => self.end(Bar, start),
                 ':' => self.end(Colon, start),
+                '~' => self.end(Tilde, start),
                 '`' => {
                     if self.number('-') {
                         self.end(Number, start)
@@ -589,6 +592,20 @@ impl Lexer {
                     while let Some(c) = self.next_char_if(is_ident_char) {
                         ident.push(c);
                     }
+                    // Qualified names like `Lib~Name`
+                    if ident.starts_with(char::is_uppercase)
+                        && self.peek_char() == Some('~')
+                        && (self.input_chars.get(self.loc.char_pos + 1))
+                            .is_some_and(|&c| is_ident_char(c))
+                    {
+                        self.next_char();
+                        ident.push('~');
+                        while let Some(c) = self.next_char_if(is_ident_char) {
+                            ident.push(c);
+                        }
+                        self.end(Ident, start);
+                        continue;
+                    }
                     // Try to parse as primitives
                     let lowercase_end = ident
                         .char_indices()
@@ -10,21 +10,31 @@ The current API should be considered deeply unstable.
 mod algorithm;
 pub mod array;
 pub mod ast;
+pub mod bench;
+pub mod bits;
+pub mod build;
 mod check;
 mod compile;
+pub mod coverage;
 mod cowslice;
 mod error;
+pub mod ffi;
 pub mod format;
 pub mod function;
 mod grid_fmt;
 pub mod lex;
+pub mod lint;
 pub mod lsp;
+mod optimize;
+pub mod package;
 pub mod parse;
 pub mod primitive;
 #[doc(hidden)]
 pub mod profile;
+pub mod profiler;
 pub mod run;
 mod sys;
+pub mod testing;
 pub mod value;
 
 use std::sync::Arc;
@@ -39,12 +49,310 @@ fn suite() {
         let entry = entry.unwrap();
         let path = entry.path();
         if path.is_file() && path.extension().is_some_and(|s| s == "ua") {
-            let mut env = Uiua::with_native_sys();
-            if let Err(e) = env.load_file(&path) {
-                panic!("Test failed in {}:\n{}", path.display(), e.show(true));
-            } else if let Some(diag) = env.take_diagnostics().into_iter().next() {
-                panic!("Test failed in {}:\n{}", path.display(), diag.show(true));
+            for mode in [run::RunMode::Normal, run::RunMode::Test] {
+                let mut env = Uiua::with_native_sys().with_mode(mode);
+                if let Err(e) = env.load_file(&path) {
+                    panic!("Test failed in {}:\n{}", path.display(), e.show(true));
+                } else if let Some(diag) = env.take_diagnostics().into_iter().next() {
+                    panic!("Test failed in {}:\n{}", path.display(), diag.show(true));
+                }
+                for res in env.take_test_results() {
+                    if let Some(e) = res.error {
+                        panic!(
+                            "Test `{}` failed in {}:\n{}",
+                            res.name,
+                            path.display(),
+                            e.show(true)
+                        );
+                    }
+                }
             }
         }
     }
 }
+
+#[test]
+fn parallel_matches_serial() {
+    let code = "
+X ← ÷7 ⇡200000
+Y ← ÷3 ⇡200000
++X Y
+√X
+/+ ↯400_500 X
+/- ↯500_400 Y
+≡(/+ ×.) ↯1000_200 X
+∵(×2 +0.1) ↯100_100 Y
+/+ X
+/+ ⌊×1000 Y
+/↥ X
+/↧ Y
+";
+    let run = |max_threads| {
+        let mut env = Uiua::with_native_sys().with_max_threads(max_threads);
+        env.load_str(code).unwrap();
+        let stack = env.take_stack();
+        stack
+            .iter()
+            .map(|val| {
+                let nums = val.as_num_array().unwrap();
+                (
+                    nums.shape.clone(),
+                    nums.data.iter().map(|n| n.to_bits()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(1), run(4));
+}
+
+#[test]
+fn imports_resolve_against_importing_file() {
+    let dir = std::env::temp_dir().join(format!("uiua-imports-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(dir.join("lib").join("lib.ua"), "&i \"helper.ua\"\n+1").unwrap();
+    std::fs::write(dir.join("lib").join("helper.ua"), "5").unwrap();
+    std::fs::write(dir.join("main.ua"), "&i \"lib/lib.ua\"").unwrap();
+    let mut env = Uiua::with_native_sys();
+    env.load_file(dir.join("main.ua")).unwrap();
+    assert_eq!(env.take_stack(), [value::Value::from(6.0)]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn private_bindings_are_not_exported() {
+    let mut env = Uiua::with_native_sys();
+    let err = env
+        .load_str("Lib ~ \"tests/modules/lib.ua\"\nLib~Helper 3")
+        .unwrap_err();
+    assert!(err.to_string().contains("no public binding `Helper`"));
+    let err = env
+        .load_str("~ \"tests/modules/lib.ua\" ~ Helper")
+        .unwrap_err();
+    assert!(err.to_string().contains("does not export `Helper`"));
+}
+
+#[test]
+fn check_mode_compiles_without_running() {
+    use run::RunMode;
+    let check = |code: &str| {
+        Uiua::with_native_sys()
+            .with_mode(RunMode::Check)
+            .load_str(code)
+    };
+    check("X ← ⍤\"ran\" 0\n⍤\"ran\" 0\nF ← +X\nF 1").unwrap();
+    assert!(check("F ← |2 ¯\nF 1 2").is_err());
+    assert!(check("Missing 1").is_err());
+    // Imports are still run so that their bindings can be checked
+    let import = "Lib ← &i \"tests/modules/lib.ua\"\nF ← use \"Square\" Lib\nF 2";
+    assert!(Uiua::with_native_sys().load_str(import).is_err());
+    assert!(check(import).is_err());
+}
+
+#[test]
+fn error_records() {
+    let err = Uiua::with_native_sys()
+        .load_str("F ← ⍤\"oops\" 0\nF")
+        .unwrap_err();
+    let records = err.records();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.kind, RecordKind::Error);
+    assert_eq!(record.message, "oops");
+    assert_eq!(record.span.unwrap().start, RecordLoc { line: 1, col: 5 });
+    assert!(record
+        .to_json()
+        .starts_with(r#"{"kind":"error","message":"oops""#));
+    let err = Uiua::with_native_sys().load_str("(\n)]").unwrap_err();
+    assert!(err.records().iter().all(|r| r.span.is_some()));
+}
+
+#[test]
+fn test_scopes_run_independently() {
+    let code = "\
+F ← +1
+~~~
+# first
+⍤.≅ 2 F 1
+~~~
+~~~
+⍤\"nope\" =0 F 1
+~~~
+~~~
+# last
+⍤.≅ 3 F 2
+~~~";
+    let mut env = Uiua::with_native_sys().with_mode(run::RunMode::Test);
+    env.load_str(code).unwrap();
+    let results = env.take_test_results();
+    let names: Vec<_> = results.iter().map(|res| res.name.as_str()).collect();
+    assert_eq!(names, ["first", "line 7", "last"]);
+    let passed: Vec<_> = results.iter().map(|res| res.passed()).collect();
+    assert_eq!(passed, [true, false, true]);
+
+    let mut env = Uiua::with_native_sys()
+        .with_mode(run::RunMode::Test)
+        .with_test_filter("la");
+    env.load_str(code).unwrap();
+    let results = env.take_test_results();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "last");
+}
+
+#[test]
+fn coverage_counts_executed_lines() {
+    let path = std::env::temp_dir().join(format!("uiua-coverage-{}.ua", std::process::id()));
+    std::fs::write(&path, "Double ← ×2\nHalf ← ÷2\nDouble 3\nX ← ×2 +1 3\n").unwrap();
+    let mut env = Uiua::with_native_sys().with_coverage();
+    env.load_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let report = env.coverage().unwrap();
+    let lines = &report.files[&path];
+    assert!(lines[&1] > 0);
+    assert_eq!(lines[&2], 0);
+    assert!(lines[&3] > 0);
+    // Constant lines are counted even though they would be folded
+    assert!(lines[&4] > 0);
+    let covered: Vec<_> = (report.bindings.iter())
+        .map(|binding| (binding.name.as_ref(), binding.covered > 0))
+        .collect();
+    assert_eq!(covered, [("Double", true), ("Half", false), ("X", true)]);
+    assert!(report.to_lcov().contains("FNDA:0,Half"));
+}
+
+#[test]
+fn mapped_files_are_not_written() {
+    use value::Value;
+    let path = std::env::temp_dir().join(format!("uiua-map-{}.bin", std::process::id()));
+    let bytes: Vec<u8> = [1.5f64, -2.0, 8.0]
+        .iter()
+        .flat_map(|n| n.to_ne_bytes())
+        .collect();
+    std::fs::write(&path, &bytes).unwrap();
+    let mut env = Uiua::with_native_sys();
+    let code = format!("P ← \"{}\"\n/+ &fmn P\n⧻ ↘8 &fmb P\n&fmn P", path.display());
+    env.load_str(&code).unwrap();
+    let mut stack = env.take_stack().into_iter();
+    assert_eq!(stack.next().unwrap(), Value::from(7.5));
+    assert_eq!(stack.next().unwrap(), Value::from(16.0));
+    let Some(Value::Num(mut nums)) = stack.next() else {
+        panic!("mapped numbers are not numbers")
+    };
+    nums.data[0] = 0.0;
+    assert_eq!(nums.data, [0.0, -2.0, 8.0]);
+    drop(nums);
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+
+    std::fs::write(&path, &bytes[..5]).unwrap();
+    let err = env.load_str(&code).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(err.to_string().contains("not a multiple of 8 bytes"));
+}
+
+#[test]
+fn memo_limit_bounds_cache() {
+    let same = |limit, code: &str| {
+        let mut env = Uiua::with_native_sys().with_memo_limit(limit);
+        env.load_str(code).unwrap();
+        env.take_stack().pop().unwrap() == 1.0.into()
+    };
+    assert!(same(1, "F ← memo(+⚂)\n=F 1 F 1"));
+    assert!(!same(0, "F ← memo(+⚂)\n=F 1 F 1"));
+    // Caching the output for 2 clears the one for 1
+    assert!(!same(1, "F ← memo(+⚂)\n=F 1 ;F 2 F 1"));
+    assert!(same(2, "F ← memo(+⚂)\n=F 1 ;F 2 F 1"));
+}
+
+#[test]
+fn memo_keys_include_fills() {
+    let mut env = Uiua::with_native_sys();
+    let res = env.load_str("F ← memo(+[1 2])\n⬚0F [1 2 3]\nF [1 2 3]");
+    assert!(res.is_err());
+}
+
+#[test]
+fn memo_call_scope_clears_after_outermost_call() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use {run::MemoScope, value::Value};
+    /// Counts printed lines, to count how often a function runs
+    #[derive(Default)]
+    struct CountingBackend(AtomicUsize);
+    impl SysBackend for CountingBackend {
+        fn any(&self) -> &dyn std::any::Any {
+            self
+        }
+        fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+            self.0.fetch_add(s.matches('\n').count(), Ordering::Relaxed);
+            Ok(())
+        }
+    }
+    let mut env = Uiua::with_backend(CountingBackend::default()).with_memo_scope(MemoScope::Call);
+    env.load_str("F ← memo(|1 &p\"\" ?∘(|1 +↬2-1∶↬2-2.) <2.)\nF 10\nF 10")
+        .unwrap();
+    assert_eq!(env.take_stack(), vec![Value::from(55.0); 2]);
+    // Recursion shares the cache, so each argument from 0 to 10 is computed once.
+    // The second call starts with an empty cache.
+    let backend = env.downcast_backend::<CountingBackend>().unwrap();
+    assert_eq!(backend.0.load(Ordering::Relaxed), 22);
+}
+
+#[test]
+fn profile_attributes_time_to_call_stacks() {
+    let mut env = Uiua::with_native_sys().with_profiling();
+    env.load_str("Sq ← ×.\nF ← /+ ≡Sq ⇡\nF 1000").unwrap();
+    let report = env.profile().unwrap();
+    for stack in [
+        "main;F;range",
+        "main;F;rows;Sq;duplicate",
+        "main;F;rows;Sq;multiply",
+    ] {
+        assert!(report.stacks.contains_key(stack), "missing {stack}");
+    }
+    let top = report.top(usize::MAX);
+    let f = top.iter().find(|entry| entry.name == "F").unwrap();
+    let sq = top.iter().find(|entry| entry.name == "Sq").unwrap();
+    assert!(f.total_time >= sq.total_time);
+    assert!(f.total_time <= report.total());
+    let folded = report.to_folded();
+    assert!(folded.lines().all(|line| line.starts_with("main")));
+}
+
+#[test]
+fn benches_run_and_compare_to_baseline() {
+    use bench::*;
+    use std::time::Duration;
+    let mut env = Uiua::with_native_sys().with_benches();
+    env.load_str("BenchSum ← /+ ⇡100\nHelper ← +1\nBenchDouble ← ×2 5")
+        .unwrap();
+    let options = BenchOptions {
+        warmup: 1,
+        runs: 5,
+        filter: None,
+    };
+    let results = env.run_benches(&options).unwrap();
+    let names: Vec<_> = results.iter().map(|res| res.name.as_ref()).collect();
+    assert_eq!(names, ["BenchSum", "BenchDouble"]);
+    assert!(results.iter().all(|res| res.samples.len() == 5));
+
+    let ms = Duration::from_millis;
+    let stats = BenchStats::new(&[ms(4), ms(1), ms(3), ms(2)]);
+    assert_eq!(stats.mean, Duration::from_micros(2500));
+    assert_eq!(stats.median, Duration::from_micros(2500));
+
+    let mut baseline = Baseline::new(&results);
+    baseline.benches.get_mut("BenchSum").unwrap().median = Duration::ZERO;
+    baseline.benches.get_mut("BenchDouble").unwrap().median = Duration::from_nanos(1);
+    let comparisons = baseline.compare(&results, 10.0);
+    let regressed: Vec<_> = (comparisons.iter())
+        .map(|comp| (comp.name.as_ref(), comp.regressed))
+        .collect();
+    assert_eq!(regressed, [("BenchSum", false), ("BenchDouble", true)]);
+}
+
+#[test]
+fn bench_bodies_are_not_folded() {
+    use function::Instr;
+    let mut env = Uiua::with_native_sys().with_benches();
+    env.load_str("X ← ⇡10\nBenchAdd ← ×X +X X").unwrap();
+    let benches = env.benches.as_ref().unwrap();
+    assert!(!matches!(benches[0].instrs.as_slice(), [Instr::Push(_)]));
+}
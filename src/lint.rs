@@ -0,0 +1,368 @@
+//! Static checks for likely mistakes and redundant code
+//!
+//! Rules can be disabled in a `.lint.ua` file, which is found the same way as `.fmt.ua`.
+//! Each rule is a binding named after the rule in `PascalCase`:
+//!
+//! ```uiua
+//! UnusedBindings ← 0
+//! ConstantExpressions ← 0
+//! ```
+
+use std::{
+    collections::HashSet,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use paste::paste;
+
+use crate::{
+    ast::{Item, Word},
+    format::FormatConfigBackend,
+    lex::{CodeSpan, Sp},
+    parse::parse,
+    primitive::Primitive,
+    Diagnostic, DiagnosticKind, Ident, Uiua, UiuaError, UiuaResult,
+};
+
+macro_rules! lint_config {
+    ($($(#[doc = $doc:literal])* $name:ident),* $(,)?) => {
+        /// The lint rules that are enabled
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct LintConfig {
+            $(
+                $(#[doc = $doc])*
+                pub $name: bool,
+            )*
+        }
+
+        impl Default for LintConfig {
+            fn default() -> Self {
+                Self {
+                    $($name: true,)*
+                }
+            }
+        }
+
+        impl LintConfig {
+            paste! {
+                /// Load the lint configuration from a file
+                pub fn from_file(path: &Path) -> UiuaResult<Self> {
+                    let mut env = Uiua::with_backend(FormatConfigBackend).print_diagnostics(true);
+                    env.load_file(path)?;
+                    let mut bindings = env.all_bindings_in_scope();
+                    let mut config = Self::default();
+                    $(
+                        if let Some(value) = bindings.remove(stringify!([<$name:camel>])) {
+                            config.$name = value.as_bool(
+                                &env,
+                                concat!(
+                                    "Lint config option '",
+                                    stringify!([<$name:camel>]),
+                                    "' expects a boolean"
+                                ),
+                            )?;
+                        }
+                    )*
+                    Ok(config)
+                }
+            }
+        }
+    };
+}
+
+lint_config!(
+    /// Warn about private and scoped bindings that are never used
+    unused_bindings,
+    /// Warn about bindings with the same name as a primitive
+    shadowed_primitives,
+    /// Point out sequences of primitives that do nothing, like `⇌⇌`
+    redundant_sequences,
+    /// Point out primitives applied only to literals
+    constant_expressions,
+    /// Warn about code that can never run because of an unconditional `⎋`
+    unreachable_code,
+);
+
+impl LintConfig {
+    /// Find the `.lint.ua` file that applies to a path and load it
+    ///
+    /// If there is none, all rules are enabled.
+    pub fn search(path: Option<&Path>) -> UiuaResult<Self> {
+        match Self::search_config_file(path) {
+            Some(file_path) => Self::from_file(&file_path),
+            None => Ok(Self::default()),
+        }
+    }
+    fn search_config_file(path: Option<&Path>) -> Option<PathBuf> {
+        let mut path = path
+            .and_then(|p| fs::canonicalize(p).ok())
+            .unwrap_or(env::current_dir().ok()?);
+        loop {
+            let file_path = path.join(".lint.ua");
+            if file_path.exists() {
+                return Some(file_path);
+            }
+            if !path.pop() {
+                return None;
+            }
+        }
+    }
+}
+
+/// Lint parsed items
+pub fn lint(items: &[Item], config: &LintConfig) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        diagnostics: Vec::new(),
+        used: HashSet::new(),
+        must_use: Vec::new(),
+    };
+    linter.items(items, true);
+    if config.unused_bindings {
+        for name in linter.must_use {
+            if !linter.used.contains(&name.value) {
+                linter.diagnostics.push(Diagnostic::new(
+                    format!("Binding `{}` is never used", name.value),
+                    name.span,
+                    DiagnosticKind::Warning,
+                ));
+            }
+        }
+    }
+    linter.diagnostics.sort();
+    linter.diagnostics
+}
+
+/// Lint a file
+pub fn lint_file(path: &Path, config: &LintConfig) -> UiuaResult<Vec<Diagnostic>> {
+    let input = fs::read_to_string(path).map_err(|e| UiuaError::Load(path.into(), e.into()))?;
+    let (items, errors, _) = parse(&input, Some(path));
+    if !errors.is_empty() {
+        return Err(UiuaError::Parse(errors));
+    }
+    Ok(lint(&items, config))
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    diagnostics: Vec<Diagnostic>,
+    used: HashSet<Ident>,
+    /// Bindings that cannot be used from outside the file
+    must_use: Vec<Sp<Ident>>,
+}
+
+impl<'a> Linter<'a> {
+    fn items(&mut self, items: &[Item], top_level: bool) {
+        for item in items {
+            match item {
+                Item::Scoped { items, .. } => self.items(items, false),
+                Item::Words(words) => {
+                    self.line(words);
+                }
+                Item::Binding(binding) => {
+                    if self.config.shadowed_primitives {
+                        let name = &binding.name;
+                        if let Some(prim) = Primitive::all().find(|prim| {
+                            (prim.name()).is_some_and(|n| n.eq_ignore_ascii_case(&name.value))
+                        }) {
+                            self.diagnostics.push(Diagnostic::new(
+                                format!(
+                                    "Binding `{}` has the same name as {prim} {}",
+                                    name.value,
+                                    prim.name().unwrap_or_default()
+                                ),
+                                name.span.clone(),
+                                DiagnosticKind::Warning,
+                            ));
+                        }
+                    }
+                    if binding.private || !top_level {
+                        self.must_use.push(binding.name.clone());
+                    }
+                    self.line(&binding.words);
+                }
+                Item::Import(_) | Item::ExtraNewlines(_) => {}
+            }
+        }
+    }
+    /// Lint a line of words, returning whether it always breaks
+    fn line(&mut self, words: &[Sp<Word>]) -> bool {
+        let words: Vec<&Sp<Word>> = (words.iter())
+            .filter(|word| !matches!(word.value, Word::Spaces | Word::Comment(_)))
+            .collect();
+        if self.config.redundant_sequences {
+            self.redundant(&words);
+        }
+        if self.config.constant_expressions {
+            self.constant(&words);
+        }
+        let breaks = self.unreachable(&words);
+        for word in words {
+            self.word(word);
+        }
+        breaks
+    }
+    fn word(&mut self, word: &Sp<Word>) {
+        match &word.value {
+            Word::Ident(ident) => {
+                self.used.insert(ident.clone());
+            }
+            Word::Strand(items) => {
+                for item in items {
+                    self.word(item);
+                }
+            }
+            Word::Array(arr) => {
+                for line in &arr.lines {
+                    self.line(line);
+                }
+            }
+            Word::Func(func) => {
+                let mut broke = false;
+                for line in &func.lines {
+                    if broke && self.config.unreachable_code {
+                        if let Some(span) = span_of(line) {
+                            self.diagnostics.push(Diagnostic::new(
+                                "Unreachable code after unconditional break",
+                                span,
+                                DiagnosticKind::Warning,
+                            ));
+                        }
+                        break;
+                    }
+                    broke = self.line(line);
+                }
+            }
+            Word::Modified(m) => {
+                for operand in &m.operands {
+                    self.word(operand);
+                }
+            }
+            _ => {}
+        }
+    }
+    fn redundant(&mut self, words: &[&Sp<Word>]) {
+        use Primitive::*;
+        if words.len() > 1 {
+            for word in words {
+                if let Word::Primitive(Identity) = word.value {
+                    self.diagnostics.push(Diagnostic::new(
+                        format!("{Identity} does nothing here"),
+                        word.span.clone(),
+                        DiagnosticKind::Style,
+                    ));
+                }
+            }
+        }
+        let mut i = 0;
+        while i + 1 < words.len() {
+            let (Word::Primitive(a), Word::Primitive(b)) = (&words[i].value, &words[i + 1].value)
+            else {
+                i += 1;
+                continue;
+            };
+            if let (Pop, Dup) | (Flip, Flip) | (Reverse, Reverse) = (a, b) {
+                self.diagnostics.push(Diagnostic::new(
+                    format!("{a}{b} does nothing and can be removed"),
+                    words[i].span.clone().merge(words[i + 1].span.clone()),
+                    DiagnosticKind::Style,
+                ));
+                i += 2;
+            } else {
+                i += 1;
+            }
+        }
+    }
+    fn constant(&mut self, words: &[&Sp<Word>]) {
+        for (i, word) in words.iter().enumerate() {
+            let Word::Primitive(prim) = word.value else {
+                continue;
+            };
+            if prim.is_modifier() || !prim.is_pure() || prim.outputs() != Some(1) {
+                continue;
+            }
+            let Some(args) = prim.args().map(usize::from).filter(|&args| args >= 2) else {
+                continue;
+            };
+            let operands = words.get(i + 1..i + 1 + args).unwrap_or_default();
+            let all_literals = operands.len() == args
+                && (operands.iter()).all(|w| matches!(w.value, Word::Number(..) | Word::Char(_)));
+            if all_literals {
+                let span = word.span.clone().merge(operands[args - 1].span.clone());
+                self.diagnostics.push(Diagnostic::new(
+                    format!("{prim} is only applied to constants, so this could be folded into a single value"),
+                    span,
+                    DiagnosticKind::Advice,
+                ));
+            }
+        }
+    }
+    /// Check for words that run after an unconditional break,
+    /// returning whether the line always breaks
+    fn unreachable(&mut self, words: &[&Sp<Word>]) -> bool {
+        let Some(i) = words.windows(2).position(|pair| {
+            matches!(pair[0].value, Word::Primitive(Primitive::Break))
+                && matches!(pair[1].value, Word::Number(_, n) if n != 0.0)
+        }) else {
+            return false;
+        };
+        if i > 0 && self.config.unreachable_code {
+            let span = words[0].span.clone().merge(words[i - 1].span.clone());
+            self.diagnostics.push(Diagnostic::new(
+                "Unreachable code before unconditional break",
+                span,
+                DiagnosticKind::Warning,
+            ));
+        }
+        true
+    }
+}
+
+fn span_of(words: &[Sp<Word>]) -> Option<CodeSpan> {
+    let mut words = (words.iter()).filter(|word| !matches!(word.value, Word::Spaces));
+    let first = words.next()?;
+    let last = words.next_back().unwrap_or(first);
+    Some(first.span.clone().merge(last.span.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::Span;
+
+    fn lint_str(input: &str, config: &LintConfig) -> Vec<String> {
+        let (items, errors, _) = parse(input, None);
+        assert!(errors.is_empty(), "{errors:?}");
+        (lint(&items, config).into_iter())
+            .map(|diag| match diag.span {
+                Span::Code(span) => format!("{}:{}", span.start.line, span.start.col),
+                Span::Builtin => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rules() {
+        let all = LintConfig::default();
+        let input = "\
+~F ← +1
+~G ← ×2
+G 3
+Range ← ⇡
+⇌⇌ ∘ ;. 5
++1 2
+/(+ ⎋1) [1 2]
+";
+        let spans = lint_str(input, &all);
+        assert_eq!(spans, ["1:2", "4:1", "5:1", "5:4", "5:6", "6:1", "7:3"]);
+        let none = LintConfig {
+            unused_bindings: false,
+            shadowed_primitives: false,
+            redundant_sequences: false,
+            constant_expressions: false,
+            unreachable_code: false,
+        };
+        assert!(lint_str(input, &none).is_empty());
+    }
+}
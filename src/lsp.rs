@@ -36,6 +36,15 @@ fn items_spans(items: &[Item]) -> Vec<Sp<SpanKind>> {
                 }
                 spans.extend(words_spans(&binding.words));
             }
+            Item::Import(import) => {
+                if let Some(name) = &import.name {
+                    spans.push(name.span.clone().sp(SpanKind::Ident));
+                }
+                spans.push(import.path.span.clone().sp(SpanKind::String));
+                spans.extend(
+                    (import.items.iter()).map(|item| item.span.clone().sp(SpanKind::Ident)),
+                );
+            }
             Item::ExtraNewlines(span) => spans.push(span.clone().sp(SpanKind::Whitespace)),
         }
     }
@@ -102,7 +111,12 @@ pub use server::run_server;
 
 #[cfg(feature = "lsp")]
 mod server {
-    use std::{collections::BTreeMap, sync::Arc};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        fs,
+        path::Path,
+        sync::Arc,
+    };
 
     use dashmap::DashMap;
     use tower_lsp::{jsonrpc::Result, lsp_types::*, *};
@@ -112,27 +126,34 @@ mod server {
     use crate::{
         format::{format_str, FormatConfig /*, FormatConfigSource*/},
         lex::Loc,
+        lint::{lint, LintConfig},
+        package::{resolve_import, Manifest, Package},
         primitive::PrimDocFragment,
-        Ident, Uiua,
+        DiagnosticKind, Ident, Uiua,
     };
 
     pub struct LspDoc {
         pub input: String,
         pub spans: Vec<Sp<SpanKind>>,
         pub bindings: BindingsInfo,
+        pub lints: Vec<crate::Diagnostic>,
     }
 
     type BindingsInfo = BTreeMap<Sp<Ident>, Arc<BindingInfo>>;
 
     impl LspDoc {
-        fn new(input: String) -> Self {
-            let (items, _, _) = parse(&input, None);
+        fn new(input: String, path: Option<&Path>, lint_config: &LintConfig) -> Self {
+            let (items, _, _) = parse(&input, path);
             let spans = items_spans(&items);
-            let bindings = bindings_info(&items);
+            let package = (path.and_then(Manifest::search))
+                .and_then(|manifest_path| Package::load(&manifest_path).ok());
+            let bindings = bindings_info(&items, path.map(|path| (path, package.as_ref())));
+            let lints = lint(&items, lint_config);
             Self {
                 input,
                 spans,
                 bindings,
+                lints,
             }
         }
     }
@@ -142,13 +163,18 @@ mod server {
         pub comment: Option<String>,
     }
 
-    fn bindings_info(items: &[Item]) -> BindingsInfo {
+    /// Collect the bindings declared and referenced in some items
+    ///
+    /// Imports are only followed if the path of the importing file,
+    /// and the package that contains it if any, are given.
+    fn bindings_info(items: &[Item], importer: Option<(&Path, Option<&Package>)>) -> BindingsInfo {
         let mut bindings = BindingsInfo::new();
+        let mut modules = HashMap::new();
         let mut scope_bindings = Vec::new();
         let mut last_comment: Option<String> = None;
         for item in items {
             match item {
-                Item::Scoped { items, .. } => scope_bindings.push(bindings_info(items)),
+                Item::Scoped { items, .. } => scope_bindings.push(bindings_info(items, importer)),
                 Item::Words(words) => {
                     if let [Sp {
                         value: Word::Comment(comment),
@@ -167,20 +193,12 @@ mod server {
                         full.push_str(comment.trim());
                     } else {
                         last_comment = None;
-                        for word in words {
-                            if let Word::Ident(ident) = &word.value {
-                                if let Some((_, info)) =
-                                    bindings.iter().rev().find(|(name, _)| name.value == *ident)
-                                {
-                                    let info = info.clone();
-                                    bindings.insert(word.span.clone().sp(ident.clone()), info);
-                                }
-                            }
-                        }
+                        words_references(words, &modules, &mut bindings);
                     }
                 }
                 Item::Binding(binding) => {
                     let comment = last_comment.take();
+                    words_references(&binding.words, &modules, &mut bindings);
                     bindings.insert(
                         binding.name.clone(),
                         BindingInfo {
@@ -190,6 +208,31 @@ mod server {
                         .into(),
                     );
                 }
+                Item::Import(import) => {
+                    last_comment = None;
+                    let exports = importer
+                        .map(|(path, package)| {
+                            module_bindings(&resolve_import(
+                                package,
+                                Some(path),
+                                &import.path.value,
+                            ))
+                        })
+                        .unwrap_or_default();
+                    for item in &import.items {
+                        let info = exports.get(&item.value).cloned().unwrap_or_else(|| {
+                            BindingInfo {
+                                comment: None,
+                                span: item.span.clone(),
+                            }
+                            .into()
+                        });
+                        bindings.insert(item.clone(), info);
+                    }
+                    if let Some(name) = &import.name {
+                        modules.insert(name.value.clone(), exports);
+                    }
+                }
                 Item::ExtraNewlines(_) => {}
             }
         }
@@ -197,6 +240,66 @@ mod server {
         scope_bindings.into_iter().flatten().collect()
     }
 
+    /// Get the public top-level bindings of a module file
+    fn module_bindings(path: &Path) -> HashMap<Ident, Arc<BindingInfo>> {
+        let Ok(input) = fs::read_to_string(path) else {
+            return HashMap::new();
+        };
+        let (items, _, _) = parse(&input, Some(path));
+        let bindings = bindings_info(&items, None);
+        (items.iter())
+            .filter_map(|item| match item {
+                Item::Binding(binding) if !binding.private => {
+                    let info = bindings.get(&binding.name)?.clone();
+                    Some((binding.name.value.clone(), info))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Record the bindings that some words refer to
+    fn words_references(
+        words: &[Sp<Word>],
+        modules: &HashMap<Ident, HashMap<Ident, Arc<BindingInfo>>>,
+        bindings: &mut BindingsInfo,
+    ) {
+        for word in words {
+            match &word.value {
+                Word::Ident(ident) => {
+                    let info = if let Some(info) =
+                        (bindings.iter().rev()).find(|(name, _)| name.value == *ident)
+                    {
+                        Some(info.1.clone())
+                    } else if let Some((module, name)) = ident.split_once('~') {
+                        modules
+                            .get(module)
+                            .and_then(|exports| exports.get(name))
+                            .cloned()
+                    } else {
+                        None
+                    };
+                    if let Some(info) = info {
+                        bindings.insert(word.span.clone().sp(ident.clone()), info);
+                    }
+                }
+                Word::Strand(items) => words_references(items, modules, bindings),
+                Word::Array(arr) => {
+                    for line in &arr.lines {
+                        words_references(line, modules, bindings);
+                    }
+                }
+                Word::Func(func) => {
+                    for line in &func.lines {
+                        words_references(line, modules, bindings);
+                    }
+                }
+                Word::Modified(m) => words_references(&m.operands, modules, bindings),
+                _ => {}
+            }
+        }
+    }
+
     pub fn run_server() {
         tokio::runtime::Builder::new_current_thread()
             .build()
@@ -220,6 +323,37 @@ mod server {
         docs: DashMap<Url, LspDoc>,
     }
 
+    impl Backend {
+        async fn update_doc(&self, uri: Url, text: String) {
+            let path = uri.to_file_path().ok();
+            let lint_config = LintConfig::search(path.as_deref()).unwrap_or_default();
+            let doc = LspDoc::new(text, path.as_deref(), &lint_config);
+            let diagnostics = (doc.lints.iter())
+                .filter_map(|diag| {
+                    let crate::lex::Span::Code(span) = &diag.span else {
+                        return None;
+                    };
+                    let severity = match diag.kind {
+                        DiagnosticKind::Warning => DiagnosticSeverity::WARNING,
+                        DiagnosticKind::Advice => DiagnosticSeverity::INFORMATION,
+                        DiagnosticKind::Style => DiagnosticSeverity::HINT,
+                    };
+                    Some(Diagnostic {
+                        range: uiua_span_to_lsp(span),
+                        severity: Some(severity),
+                        source: Some("uiua lint".into()),
+                        message: diag.message.clone(),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+            self.docs.insert(uri.clone(), doc);
+            self.client
+                .publish_diagnostics(uri, diagnostics, None)
+                .await;
+        }
+    }
+
     #[tower_lsp::async_trait]
     impl LanguageServer for Backend {
         async fn initialize(&self, _params: InitializeParams) -> Result<InitializeResult> {
@@ -229,6 +363,7 @@ mod server {
                         TextDocumentSyncKind::FULL,
                     )),
                     hover_provider: Some(HoverProviderCapability::Simple(true)),
+                    definition_provider: Some(OneOf::Left(true)),
                     document_formatting_provider: Some(OneOf::Left(true)),
                     semantic_tokens_provider: Some(
                         SemanticTokensServerCapabilities::SemanticTokensOptions(
@@ -260,17 +395,16 @@ mod server {
         }
 
         async fn did_open(&self, param: DidOpenTextDocumentParams) {
-            self.docs.insert(
-                param.text_document.uri,
-                LspDoc::new(param.text_document.text),
-            );
+            self.update_doc(param.text_document.uri, param.text_document.text)
+                .await;
         }
 
         async fn did_change(&self, params: DidChangeTextDocumentParams) {
-            self.docs.insert(
+            self.update_doc(
                 params.text_document.uri,
-                LspDoc::new(params.content_changes[0].text.clone()),
-            );
+                params.content_changes[0].text.clone(),
+            )
+            .await;
         }
 
         async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -350,6 +484,32 @@ mod server {
             }))
         }
 
+        async fn goto_definition(
+            &self,
+            params: GotoDefinitionParams,
+        ) -> Result<Option<GotoDefinitionResponse>> {
+            let uri = &params.text_document_position_params.text_document.uri;
+            let doc = if let Some(doc) = self.docs.get(uri) {
+                doc
+            } else {
+                return Ok(None);
+            };
+            let (line, col) = lsp_pos_to_uiua(params.text_document_position_params.position);
+            let Some((_, binding)) =
+                (doc.bindings.iter()).find(|(ident, _)| ident.span.contains_line_col(line, col))
+            else {
+                return Ok(None);
+            };
+            // Bindings from other files carry those files' paths
+            let uri = (binding.span.path.as_deref())
+                .and_then(|path| Url::from_file_path(path).ok())
+                .unwrap_or_else(|| uri.clone());
+            Ok(Some(GotoDefinitionResponse::Scalar(Location {
+                uri,
+                range: uiua_span_to_lsp(&binding.span),
+            })))
+        }
+
         async fn formatting(
             &self,
             params: DocumentFormattingParams,
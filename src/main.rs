@@ -18,14 +18,33 @@ use notify::{EventKind, RecursiveMode, Watcher};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use uiua::{
-    format::{format_file, FormatConfig, FormatConfigSource},
+    bench::{Baseline, BenchComparison, BenchOptions, BenchResult},
+    build::{bundle, EmbeddedProgram},
+    coverage::CoverageReport,
+    format::{format_file, format_items, FormatConfig, FormatConfigSource},
+    lint::{lint_file, LintConfig},
+    package::{Manifest, Package, MANIFEST_FILE},
+    profiler::ProfileReport,
     run::RunMode,
-    Uiua, UiuaError, UiuaResult,
+    testing::{junit_xml, TestResult},
+    DiagnosticKind, Uiua, UiuaError, UiuaResult,
 };
 
 fn main() {
     color_backtrace::install();
 
+    // Executables made with `uiua build` run their embedded program
+    if let Some(program) = env::current_exe()
+        .ok()
+        .and_then(|exe| EmbeddedProgram::from_executable(&exe))
+    {
+        if let Err(e) = run_embedded(program) {
+            println!("{}", e.show(true));
+            exit(1);
+        }
+        return;
+    }
+
     let _ = ctrlc::set_handler(|| {
         let mut child = WATCH_CHILD.lock();
         if let Some(ch) = &mut *child {
@@ -42,13 +61,30 @@ fn main() {
     });
 
     if let Err(e) = run() {
-        println!("{}", e.show(true));
+        let message_format = App::try_parse()
+            .map(|app| app.message_format())
+            .unwrap_or_default();
+        print_error(&e, message_format);
         exit(1);
     }
 }
 
 static WATCH_CHILD: Lazy<Mutex<Option<Child>>> = Lazy::new(Default::default);
 
+fn run_embedded(program: EmbeddedProgram) -> UiuaResult {
+    let input = program.files[&program.main].clone();
+    let mut rt = Uiua::with_native_sys()
+        .with_file_path(&program.main)
+        .with_args(env::args().skip(1).collect())
+        .with_embedded_files(program.files)
+        .print_diagnostics(true);
+    rt.load_str_path(&input, &program.main)?;
+    for value in rt.take_stack() {
+        println!("{}", value.show());
+    }
+    Ok(())
+}
+
 fn run() -> UiuaResult {
     if cfg!(feature = "profile") {
         uiua::profile::run_profile();
@@ -63,10 +99,25 @@ fn run() -> UiuaResult {
                 } else {
                     fs::write("main.ua", "\"Hello, World!\"").unwrap();
                 }
+                if Path::new(MANIFEST_FILE).exists() {
+                    eprintln!("File already exists: {MANIFEST_FILE}");
+                } else {
+                    let name = env::current_dir()
+                        .ok()
+                        .and_then(|dir| Some(dir.file_name()?.to_string_lossy().into_owned()))
+                        .unwrap_or_else(|| "main".into());
+                    fs::write(MANIFEST_FILE, Manifest::new(name).to_toml()).unwrap();
+                }
             }
+            App::Lock => match Manifest::search(Path::new(".")) {
+                Some(manifest_path) => Package::load(&manifest_path)?.write_lock()?,
+                None => eprintln!("No {MANIFEST_FILE} found. Create one with `uiua init`"),
+            },
             App::Fmt {
                 path,
                 formatter_options,
+                // Format errors are returned and printed by `main` in this format
+                message_format: _,
             } => {
                 let config = FormatConfig::from_source(
                     formatter_options.format_config_source,
@@ -85,6 +136,9 @@ fn run() -> UiuaResult {
                 formatter_options,
                 no_update,
                 mode,
+                message_format,
+                profile,
+                profile_top,
                 #[cfg(feature = "audio")]
                 audio_options,
                 args,
@@ -113,16 +167,138 @@ fn run() -> UiuaResult {
                 let mode = mode.unwrap_or(RunMode::Normal);
                 #[cfg(feature = "audio")]
                 setup_audio(audio_options);
-                let mut rt = Uiua::with_native_sys()
+                let mut rt = with_package(Uiua::with_native_sys(), &path)?
                     .with_mode(mode)
                     .with_file_path(&path)
                     .with_args(args)
-                    .print_diagnostics(true);
-                rt.load_file(path)?;
+                    .print_diagnostics(message_format == MessageFormat::Human);
+                if profile.is_some() {
+                    rt = rt.with_profiling();
+                }
+                let res = rt.load_file(path);
+                print_diagnostics(&mut rt, message_format);
+                if let Some((folded_path, report)) = profile.zip(rt.profile()) {
+                    fs::write(&folded_path, report.to_folded())
+                        .map_err(|e| UiuaError::Load(folded_path, e.into()))?;
+                    if message_format == MessageFormat::Human {
+                        print_profile(&report, profile_top);
+                    }
+                }
+                res?;
                 for value in rt.take_stack() {
                     println!("{}", value.show());
                 }
             }
+            App::Build { path, output } => {
+                let path = if let Some(path) = path {
+                    path
+                } else {
+                    match working_file_path() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return Ok(());
+                        }
+                    }
+                };
+                let package = match Manifest::search(&path) {
+                    Some(manifest_path) => Some(Package::load(&manifest_path)?),
+                    None => None,
+                };
+                let (program, dynamic) = EmbeddedProgram::collect(&path, package.as_ref())?;
+                for span in dynamic {
+                    eprintln!(
+                        "Warning: the import at {span} does not use a string literal, \
+                        so its file will not be embedded"
+                    );
+                }
+                let output = output.unwrap_or_else(|| {
+                    let stem = path.file_stem().unwrap_or_default();
+                    PathBuf::from(stem).with_extension(env::consts::EXE_EXTENSION)
+                });
+                let exe =
+                    env::current_exe().map_err(|e| UiuaError::Load(path.clone(), e.into()))?;
+                program
+                    .write_executable(&exe, &output)
+                    .map_err(|e| UiuaError::Load(output.clone(), e.into()))?;
+                println!(
+                    "Built {} ({} file{})",
+                    output.display(),
+                    program.files.len(),
+                    if program.files.len() == 1 { "" } else { "s" }
+                );
+            }
+            App::Bundle {
+                path,
+                output,
+                format_config_source,
+            } => {
+                let path = if let Some(path) = path {
+                    path
+                } else {
+                    match working_file_path() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return Ok(());
+                        }
+                    }
+                };
+                let package = match Manifest::search(&path) {
+                    Some(manifest_path) => Some(Package::load(&manifest_path)?),
+                    None => None,
+                };
+                let items = bundle(&path, package.as_ref())?;
+                let config = FormatConfig::from_source(format_config_source, Some(&path))?;
+                let bundled = format_items(&items, &config).output;
+                if let Some(output) = output {
+                    fs::write(&output, bundled).map_err(|e| UiuaError::Format(output, e.into()))?;
+                } else {
+                    println!("{bundled}");
+                }
+            }
+            App::Check {
+                paths,
+                warnings_as_errors,
+                message_format,
+            } => {
+                let paths = if paths.is_empty() {
+                    uiua_files()
+                } else {
+                    paths
+                };
+                let failures = (paths.iter())
+                    .filter(|path| !check_file(path, warnings_as_errors, message_format))
+                    .count();
+                let s = if paths.len() == 1 { "" } else { "s" };
+                if failures > 0 {
+                    eprintln!("{failures} of {} file{s} failed to check", paths.len());
+                    exit(1);
+                }
+                if message_format == MessageFormat::Human {
+                    println!("Checked {} file{s} with no errors", paths.len());
+                }
+            }
+            App::Lint { paths } => {
+                let paths = if paths.is_empty() {
+                    uiua_files()
+                } else {
+                    paths
+                };
+                let mut count = 0;
+                for path in &paths {
+                    let config = LintConfig::search(Some(path))?;
+                    for diag in lint_file(path, &config)? {
+                        println!("{}", diag.show(true));
+                        count += 1;
+                    }
+                }
+                if count > 0 {
+                    let s = if count == 1 { "" } else { "s" };
+                    eprintln!("{count} lint{s} found");
+                    exit(1);
+                }
+            }
             App::Eval {
                 code,
                 #[cfg(feature = "audio")]
@@ -131,7 +307,7 @@ fn run() -> UiuaResult {
             } => {
                 #[cfg(feature = "audio")]
                 setup_audio(audio_options);
-                let mut rt = Uiua::with_native_sys()
+                let mut rt = with_package(Uiua::with_native_sys(), Path::new("."))?
                     .with_mode(RunMode::Normal)
                     .with_args(args)
                     .print_diagnostics(true);
@@ -143,6 +319,10 @@ fn run() -> UiuaResult {
             App::Test {
                 path,
                 formatter_options,
+                message_format,
+                filter,
+                junit,
+                coverage,
             } => {
                 let path = if let Some(path) = path {
                     path
@@ -158,11 +338,81 @@ fn run() -> UiuaResult {
                 let config =
                     FormatConfig::from_source(formatter_options.format_config_source, Some(&path))?;
                 format_file(&path, &config)?;
-                Uiua::with_native_sys()
+                let mut rt = with_package(Uiua::with_native_sys(), &path)?
                     .with_mode(RunMode::Test)
-                    .print_diagnostics(true)
-                    .load_file(path)?;
-                println!("No failures!");
+                    .print_diagnostics(message_format == MessageFormat::Human);
+                if let Some(filter) = filter {
+                    rt = rt.with_test_filter(filter);
+                }
+                if coverage.is_some() {
+                    rt = rt.with_coverage();
+                }
+                let res = rt.load_file(&path);
+                print_diagnostics(&mut rt, message_format);
+                res?;
+                let results = rt.take_test_results();
+                if let Some(junit) = junit {
+                    fs::write(&junit, junit_xml(&path, &results))
+                        .map_err(|e| UiuaError::Load(junit, e.into()))?;
+                }
+                let passed = report_tests(&results, message_format);
+                if let Some((lcov_path, report)) = coverage.zip(rt.coverage()) {
+                    fs::write(&lcov_path, report.to_lcov())
+                        .map_err(|e| UiuaError::Load(lcov_path, e.into()))?;
+                    if message_format == MessageFormat::Human {
+                        print_coverage(&report);
+                    }
+                }
+                if !passed {
+                    exit(1);
+                }
+            }
+            App::Bench {
+                path,
+                filter,
+                warmup,
+                runs,
+                save_baseline,
+                baseline,
+                threshold,
+            } => {
+                let path = if let Some(path) = path {
+                    path
+                } else {
+                    match working_file_path() {
+                        Ok(path) => path,
+                        Err(e) => {
+                            eprintln!("{}", e);
+                            return Ok(());
+                        }
+                    }
+                };
+                let mut rt = with_package(Uiua::with_native_sys(), &path)?
+                    .with_benches()
+                    .print_diagnostics(true);
+                rt.load_file(&path)?;
+                rt.take_stack();
+                let options = BenchOptions {
+                    warmup,
+                    runs,
+                    filter,
+                };
+                let results = rt.run_benches(&options)?;
+                if results.is_empty() {
+                    println!("No benchmarks found. Benchmarks are bindings whose names start with `Bench`.");
+                    return Ok(());
+                }
+                let comparisons = match baseline {
+                    Some(baseline) => Baseline::load(&baseline)?.compare(&results, threshold),
+                    None => Vec::new(),
+                };
+                report_benches(&results, &comparisons);
+                if let Some(save_path) = save_baseline {
+                    Baseline::new(&results).save(&save_path)?;
+                }
+                if comparisons.iter().any(|comp| comp.regressed) {
+                    exit(1);
+                }
             }
             App::Watch {
                 no_format,
@@ -223,6 +473,210 @@ fn run() -> UiuaResult {
     Ok(())
 }
 
+/// Compile a file without running it, and print its errors and diagnostics
+///
+/// Returns whether the check passed
+fn check_file(path: &Path, warnings_as_errors: bool, message_format: MessageFormat) -> bool {
+    let res = with_package(Uiua::with_native_sys(), path).and_then(|rt| {
+        let mut rt = rt.with_mode(RunMode::Check).with_file_path(path);
+        rt.load_file(path)?;
+        Ok(rt.take_diagnostics())
+    });
+    match res {
+        Ok(diagnostics) => {
+            let mut passed = true;
+            for diag in diagnostics {
+                match message_format {
+                    MessageFormat::Human => println!("{}", diag.show(true)),
+                    MessageFormat::Json => println!("{}", diag.record().to_json()),
+                }
+                passed &= !(warnings_as_errors && diag.kind == DiagnosticKind::Warning);
+            }
+            passed
+        }
+        Err(e) => {
+            print_error(&e, message_format);
+            false
+        }
+    }
+}
+
+/// Print the results of tests, returning whether they all passed
+fn report_tests(results: &[TestResult], message_format: MessageFormat) -> bool {
+    let failed: Vec<&TestResult> = results.iter().filter(|res| !res.passed()).collect();
+    if message_format == MessageFormat::Json {
+        for res in &failed {
+            print_error(res.error.as_ref().unwrap(), message_format);
+        }
+        return failed.is_empty();
+    }
+    for res in results {
+        let millis = res.duration.as_secs_f64() * 1000.0;
+        if res.passed() {
+            println!("{} {} ({millis:.1}ms)", "✓".bright_green(), res.name);
+        } else {
+            println!("{} {} ({millis:.1}ms)", "✗".bright_red(), res.name);
+        }
+    }
+    for res in &failed {
+        println!(
+            "\n{}:\n{}",
+            res.name.bold(),
+            res.error.as_ref().unwrap().show(true)
+        );
+    }
+    let total: f64 = results.iter().map(|res| res.duration.as_secs_f64()).sum();
+    let passed = results.len() - failed.len();
+    let summary = format!("{passed} passed, {} failed in {:.2}s", failed.len(), total);
+    if failed.is_empty() {
+        println!("\n{}", summary.bright_green());
+    } else {
+        println!("\n{}", summary.bright_red());
+    }
+    failed.is_empty()
+}
+
+fn report_benches(results: &[BenchResult], comparisons: &[BenchComparison]) {
+    let millis = |d: Duration| d.as_secs_f64() * 1000.0;
+    let width = (results.iter())
+        .map(|res| res.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    println!(
+        "{:width$}  {:>12}  {:>12}  {:>12}",
+        "Name", "Mean", "Median", "Std. dev."
+    );
+    for res in results {
+        let mut line = format!(
+            "{:width$}  {:>10.3}ms  {:>10.3}ms  {:>10.3}ms",
+            res.name,
+            millis(res.stats.mean),
+            millis(res.stats.median),
+            millis(res.stats.stddev)
+        );
+        if let Some(comp) = comparisons.iter().find(|comp| comp.name == res.name) {
+            let change = format!("{:+.1}% from {:.3}ms", comp.change, millis(comp.baseline));
+            if comp.regressed {
+                line = format!("{line}  {}", change.bright_red());
+            } else if comp.change < 0.0 {
+                line = format!("{line}  {}", change.bright_green());
+            } else {
+                line = format!("{line}  {change}");
+            }
+        }
+        println!("{line}");
+    }
+    let regressions = comparisons.iter().filter(|comp| comp.regressed).count();
+    if regressions > 0 {
+        let summary = format!(
+            "\n{regressions} benchmark{} regressed",
+            if regressions == 1 { "" } else { "s" }
+        );
+        println!("{}", summary.bright_red());
+    }
+}
+
+fn print_coverage(report: &CoverageReport) {
+    let percent = |covered: usize, total: usize| {
+        if total == 0 {
+            100.0
+        } else {
+            covered as f64 / total as f64 * 100.0
+        }
+    };
+    println!("\nCoverage:");
+    for (path, lines) in &report.files {
+        let hit = lines.values().filter(|&&hits| hits > 0).count();
+        println!(
+            "  {}: {hit}/{} lines ({:.1}%)",
+            path.display(),
+            lines.len(),
+            percent(hit, lines.len())
+        );
+        let bindings = (report.bindings.iter()).filter(|binding| &binding.file == path);
+        for binding in bindings {
+            let line = format!(
+                "    {}: {}/{} ({:.1}%)",
+                binding.name,
+                binding.covered,
+                binding.total,
+                percent(binding.covered, binding.total)
+            );
+            if binding.covered < binding.total {
+                println!("{}", line.bright_red());
+            } else {
+                println!("{line}");
+            }
+        }
+    }
+    let (found, hit) = report.line_counts();
+    println!("  Total: {hit}/{found} lines ({:.1}%)", percent(hit, found));
+}
+
+fn print_profile(report: &ProfileReport, top: usize) {
+    let total = report.total().as_secs_f64();
+    let entries = report.top(top);
+    let width = (entries.iter())
+        .map(|entry| entry.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    eprintln!("\n{:width$}  {:>18}  {:>18}", "Name", "Self", "Total");
+    let percent = |time: f64| {
+        if total == 0.0 {
+            0.0
+        } else {
+            time / total * 100.0
+        }
+    };
+    for entry in entries {
+        let self_time = entry.self_time.as_secs_f64();
+        let total_time = entry.total_time.as_secs_f64();
+        eprintln!(
+            "{:width$}  {:>8.3}ms  {:>5.1}%  {:>8.3}ms  {:>5.1}%",
+            entry.name,
+            self_time * 1000.0,
+            percent(self_time),
+            total_time * 1000.0,
+            percent(total_time)
+        );
+    }
+}
+
+/// Print the diagnostics a runtime collected if they were not already printed
+fn print_diagnostics(rt: &mut Uiua, message_format: MessageFormat) {
+    if message_format == MessageFormat::Json {
+        for diag in rt.take_diagnostics() {
+            println!("{}", diag.record().to_json());
+        }
+    }
+}
+
+fn print_error(error: &UiuaError, message_format: MessageFormat) {
+    match message_format {
+        MessageFormat::Human => println!("{}", error.show(true)),
+        MessageFormat::Json => {
+            for record in error.records() {
+                println!("{}", record.to_json());
+            }
+        }
+    }
+}
+
+/// Resolve the runtime's imports against the package that contains a path, if any
+fn with_package(rt: Uiua, path: &Path) -> UiuaResult<Uiua> {
+    let Some(manifest_path) = Manifest::search(path) else {
+        return Ok(rt);
+    };
+    let package = Package::load(&manifest_path)?;
+    // An outdated lockfile should not keep the code from running
+    if let Err(e) = package.check_lock() {
+        eprintln!("Warning: {}", e.message());
+    }
+    Ok(rt.with_package(package))
+}
+
 #[derive(Debug)]
 enum NoWorkingFile {
     NoFile,
@@ -413,8 +867,10 @@ fn watch(
 #[derive(Parser)]
 #[clap(version)]
 enum App {
-    #[clap(about = "Initialize a new main.ua file")]
+    #[clap(about = "Initialize a new main.ua file and uiua.toml manifest")]
     Init,
+    #[clap(about = "Update uiua.lock with the current contents of dependencies")]
+    Lock,
     #[clap(about = "Format and run a file")]
     Run {
         path: Option<PathBuf>,
@@ -426,12 +882,69 @@ enum App {
         no_update: bool,
         #[clap(long, help = "Run the file in a specific mode")]
         mode: Option<RunMode>,
+        #[clap(
+            long,
+            value_enum,
+            default_value_t,
+            help = "How to print errors and diagnostics"
+        )]
+        message_format: MessageFormat,
+        #[clap(
+            long,
+            num_args = 0..=1,
+            default_missing_value = "profile.folded",
+            help = "Measure where time is spent and write folded stacks for flamegraph tools (default is profile.folded)"
+        )]
+        profile: Option<PathBuf>,
+        #[clap(
+            long,
+            default_value_t = 10,
+            help = "How many entries to show in the profile table"
+        )]
+        profile_top: usize,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
         #[clap(trailing_var_arg = true)]
         args: Vec<String>,
     },
+    #[clap(about = "Build a standalone executable that runs a file")]
+    Build {
+        path: Option<PathBuf>,
+        #[clap(short, long, help = "The path of the executable")]
+        output: Option<PathBuf>,
+    },
+    #[clap(about = "Bundle a file and everything it imports into a single file")]
+    Bundle {
+        path: Option<PathBuf>,
+        #[clap(short, long, help = "The path of the bundled file (default is stdout)")]
+        output: Option<PathBuf>,
+        #[clap(
+            long = "format-config",
+            default_value_t = FormatConfigSource::SearchFile,
+            help = "Select the formatter configuration source (one of search-file, default, or a path to a fmt.ua file)"
+        )]
+        format_config_source: FormatConfigSource,
+    },
+    #[clap(about = "Check files for errors without running them (imports are still run)")]
+    Check {
+        #[clap(help = "The files to check (default is all .ua files in the current directory)")]
+        paths: Vec<PathBuf>,
+        #[clap(long, help = "Treat warnings as errors")]
+        warnings_as_errors: bool,
+        #[clap(
+            long,
+            value_enum,
+            default_value_t,
+            help = "How to print errors and diagnostics"
+        )]
+        message_format: MessageFormat,
+    },
+    #[clap(about = "Check files for likely mistakes and redundant code")]
+    Lint {
+        #[clap(help = "The files to lint (default is all .ua files in the current directory)")]
+        paths: Vec<PathBuf>,
+    },
     #[clap(about = "Evaluate an expression and print its output")]
     Eval {
         code: String,
@@ -446,6 +959,52 @@ enum App {
         path: Option<PathBuf>,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
+        #[clap(
+            long,
+            value_enum,
+            default_value_t,
+            help = "How to print errors and diagnostics"
+        )]
+        message_format: MessageFormat,
+        #[clap(long, help = "Only run tests whose names contain this string")]
+        filter: Option<String>,
+        #[clap(long, help = "Write a JUnit XML report to this path")]
+        junit: Option<PathBuf>,
+        #[clap(
+            long,
+            num_args = 0..=1,
+            default_missing_value = "lcov.info",
+            help = "Track which code the tests run and write an lcov report (default is lcov.info)"
+        )]
+        coverage: Option<PathBuf>,
+    },
+    #[clap(about = "Run the benchmarks in a file")]
+    Bench {
+        path: Option<PathBuf>,
+        #[clap(long, help = "Only run benchmarks whose names contain this string")]
+        filter: Option<String>,
+        #[clap(
+            long,
+            default_value_t = 3,
+            help = "How many times to run each benchmark before measuring"
+        )]
+        warmup: usize,
+        #[clap(
+            long,
+            default_value_t = 20,
+            help = "How many times to measure each benchmark"
+        )]
+        runs: usize,
+        #[clap(long, help = "Save the results as a baseline to this path")]
+        save_baseline: Option<PathBuf>,
+        #[clap(long, help = "Compare the results against the baseline at this path")]
+        baseline: Option<PathBuf>,
+        #[clap(
+            long,
+            default_value_t = 10.0,
+            help = "How many percent slower than the baseline a benchmark must be to count as a regression"
+        )]
+        threshold: f64,
     },
     #[clap(about = "Run .ua files in the current directory when they change")]
     Watch {
@@ -467,12 +1026,41 @@ enum App {
         path: Option<PathBuf>,
         #[clap(flatten)]
         formatter_options: FormatterOptions,
+        #[clap(
+            long,
+            value_enum,
+            default_value_t,
+            help = "How to print errors and diagnostics"
+        )]
+        message_format: MessageFormat,
     },
     #[cfg(feature = "lsp")]
     #[clap(about = "Run the Language Server")]
     Lsp,
 }
 
+impl App {
+    fn message_format(&self) -> MessageFormat {
+        match self {
+            App::Run { message_format, .. }
+            | App::Check { message_format, .. }
+            | App::Test { message_format, .. }
+            | App::Fmt { message_format, .. } => *message_format,
+            _ => MessageFormat::Human,
+        }
+    }
+}
+
+/// How errors and diagnostics are printed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum MessageFormat {
+    /// Formatted reports for people to read
+    #[default]
+    Human,
+    /// One JSON record per line
+    Json,
+}
+
 #[derive(clap::Args)]
 struct FormatterOptions {
     #[clap(
@@ -0,0 +1,566 @@
+//! Optimizations of compiled instructions
+//!
+//! Once a function's instructions are compiled, common idioms are fused into
+//! dedicated kernels, sequences that do nothing are removed, and pure primitives
+//! applied only to constants are folded into the values they produce.
+//!
+//! An optimization is never allowed to change a function's signature.
+//! If it would, the unoptimized instructions are kept.
+
+use std::{cmp::Ordering, fmt};
+
+use crate::{
+    algorithm::{lazy, loops},
+    array::{Array, ArrayValue},
+    check::instrs_signature,
+    function::Instr,
+    primitive::Primitive,
+    value::Value,
+    Uiua, UiuaResult,
+};
+
+/// An idiom that is run by a dedicated kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Fused {
+    /// `⊏⍏.`
+    Sort,
+    /// `⊏⍖.`
+    SortDescending,
+    /// `⊢⍏`
+    FirstRise,
+    /// `⊢⍖`
+    FirstFall,
+    /// `/+`
+    Sum,
+    /// `≡f◫`
+    RowsWindows,
+    /// `/f◫`
+    ReduceWindows,
+    /// `⇡` followed by a primitive that does not need the range's elements
+    Range,
+    /// `↯` followed by a primitive that does not need the elements of a constant-filled array
+    Reshape,
+}
+
+impl fmt::Display for Fused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Primitive::*;
+        match self {
+            Fused::Sort => write!(f, "{Select}{Rise}{Dup}"),
+            Fused::SortDescending => write!(f, "{Select}{Fall}{Dup}"),
+            Fused::FirstRise => write!(f, "{First}{Rise}"),
+            Fused::FirstFall => write!(f, "{First}{Fall}"),
+            Fused::Sum => write!(f, "{Reduce}{Add}"),
+            Fused::RowsWindows => write!(f, "{Rows}{Windows}"),
+            Fused::ReduceWindows => write!(f, "{Reduce}{Windows}"),
+            Fused::Range => write!(f, "{Range}"),
+            Fused::Reshape => write!(f, "{Reshape}"),
+        }
+    }
+}
+
+impl Fused {
+    /// Run the kernel, falling back to the instructions it replaces for inputs it does not handle
+    pub(crate) fn run(&self, instrs: &[Instr], env: &mut Uiua) -> UiuaResult {
+        if !self.run_kernel(instrs, env)? {
+            for instr in instrs {
+                match instr {
+                    Instr::Push(val) => env.push(Value::clone(val)),
+                    &Instr::Prim(prim, span) => {
+                        env.push_span(span, Some(prim));
+                        prim.run(env)?;
+                        env.pop_span();
+                    }
+                    _ => unreachable!("fused instructions are only pushes and primitives"),
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Run the kernel, returning `false` with the stack untouched if it does not handle the inputs
+    fn run_kernel(&self, instrs: &[Instr], env: &mut Uiua) -> UiuaResult<bool> {
+        match self {
+            Fused::Range => return lazy::consume_range(&instrs[1..], env),
+            Fused::Reshape => return lazy::consume_reshape(&instrs[1..], env),
+            _ => {}
+        }
+        if let [.., Instr::Push(f), Instr::Prim(prim, span)] = instrs {
+            if let Fused::RowsWindows | Fused::ReduceWindows = self {
+                env.push_span(*span, Some(*prim));
+                let done = if *self == Fused::RowsWindows {
+                    loops::rows_windows(f, env)?
+                } else {
+                    loops::reduce_windows(f, env)?
+                };
+                env.pop_span();
+                return Ok(done);
+            }
+        }
+        let mut val = env.pop_packed(1)?;
+        let boxed = matches!(val, Value::Func(_));
+        match self {
+            Fused::Sort | Fused::SortDescending if !boxed && val.rank() == 1 => {
+                let descending = *self == Fused::SortDescending;
+                val.generic_mut(
+                    |arr| sort(arr, descending),
+                    |arr| sort(arr, descending),
+                    |arr| sort(arr, descending),
+                    |arr| sort(arr, descending),
+                );
+                env.push(val);
+                return Ok(true);
+            }
+            Fused::FirstRise | Fused::FirstFall
+                if !boxed && val.rank() == 1 && val.row_count() > 0 =>
+            {
+                let order = if *self == Fused::FirstRise {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+                let index = val.generic_ref(
+                    |arr| first_by(arr, order),
+                    |arr| first_by(arr, order),
+                    |arr| first_by(arr, order),
+                    |arr| first_by(arr, order),
+                );
+                env.push(index as f64);
+                return Ok(true);
+            }
+            Fused::Sum => match &val {
+                Value::Byte(bytes) if bytes.rank() == 1 => {
+                    env.push(loops::sum_bytes(env, &bytes.data));
+                    return Ok(true);
+                }
+                Value::Bits(bits) if bits.rank() == 1 => {
+                    env.push(bits.count_ones() as f64);
+                    return Ok(true);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        env.push(val);
+        Ok(false)
+    }
+}
+
+fn sort<T: ArrayValue>(arr: &mut Array<T>, descending: bool) {
+    if descending {
+        arr.data.sort_by(|a, b| a.array_cmp(b).reverse());
+    } else {
+        arr.data.sort_by(|a, b| a.array_cmp(b));
+    }
+}
+
+/// Find the index of the first element that is ordered before or after all others
+fn first_by<T: ArrayValue>(arr: &Array<T>, order: Ordering) -> usize {
+    let mut best = 0;
+    for (i, x) in arr.data.iter().enumerate().skip(1) {
+        if x.array_cmp(&arr.data[best]) == order {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Optimize a function's instructions
+pub(crate) fn optimize(instrs: Vec<Instr>, env: &mut Uiua) -> Vec<Instr> {
+    let Ok(sig) = instrs_signature(&instrs) else {
+        return instrs;
+    };
+    let mut fused = Vec::with_capacity(instrs.len());
+    let mut open_arrays = 0usize;
+    for instr in instrs.iter().cloned() {
+        match instr {
+            Instr::BeginArray => open_arrays += 1,
+            Instr::EndArray { .. } => open_arrays = open_arrays.saturating_sub(1),
+            _ => {}
+        }
+        fuse(&mut fused, instr, open_arrays > 0);
+    }
+    let folded = if env.fold {
+        let mut folded = Vec::with_capacity(fused.len());
+        for instr in fused {
+            fold(&mut folded, instr, env);
+        }
+        folded
+    } else {
+        fused
+    };
+    if instrs_signature(&folded) == Ok(sig) {
+        folded
+    } else {
+        instrs
+    }
+}
+
+/// Push an instruction, fusing it with the ones before it or removing them if they form a known pattern
+///
+/// Inside an array, instructions that move values around are not removed,
+/// because they may move values from outside the array into it.
+fn fuse(instrs: &mut Vec<Instr>, instr: Instr, in_array: bool) {
+    use Primitive::*;
+    match (instrs.as_mut_slice(), instr) {
+        // Cosine
+        ([.., Instr::Prim(Eta, _), Instr::Prim(Add, _)], Instr::Prim(Sin, span)) => {
+            instrs.pop();
+            instrs.pop();
+            instrs.push(Instr::Prim(Cos, span));
+        }
+        // First reverse = last, which may itself be fused with what comes before
+        ([.., Instr::Prim(Reverse, span)], Instr::Prim(First, _)) => {
+            let span = *span;
+            instrs.pop();
+            fuse(instrs, Instr::Prim(Last, span), in_array);
+        }
+        // Sequences that do nothing
+        (_, Instr::Prim(Identity, _)) if !in_array => {}
+        ([.., Instr::Prim(Reverse, _)], Instr::Prim(Reverse, _))
+        | ([.., Instr::Prim(Flip, _)], Instr::Prim(Flip, _))
+            if !in_array =>
+        {
+            instrs.pop();
+        }
+        // Sort
+        (
+            [.., Instr::Prim(Dup, _), Instr::Prim(grade @ (Rise | Fall), _)],
+            instr @ Instr::Prim(Select, _),
+        ) => {
+            let kernel = if *grade == Rise {
+                Fused::Sort
+            } else {
+                Fused::SortDescending
+            };
+            replace(instrs, 2, kernel, instr);
+        }
+        // Index of the minimum or maximum
+        ([.., Instr::Prim(grade @ (Rise | Fall), _)], instr @ Instr::Prim(First, _)) => {
+            let kernel = if *grade == Rise {
+                Fused::FirstRise
+            } else {
+                Fused::FirstFall
+            };
+            replace(instrs, 1, kernel, instr);
+        }
+        // Ranges that do not need to be materialized
+        (
+            [.., Instr::Prim(Range, _)],
+            instr @ Instr::Prim(Len | Shape | First | Last | Select, _),
+        ) => {
+            replace(instrs, 1, Fused::Range, instr);
+        }
+        ([.., Instr::Prim(Range, _), Instr::Push(f)], instr @ Instr::Prim(Reduce | Table, _))
+            if f.as_function().is_some() =>
+        {
+            replace(instrs, 2, Fused::Range, instr);
+        }
+        // Constant-filled reshapes that do not need to be materialized
+        ([.., Instr::Prim(Reshape, _)], instr @ Instr::Prim(Len | Shape | First | Last, _)) => {
+            replace(instrs, 1, Fused::Reshape, instr);
+        }
+        ([.., Instr::Prim(Reshape, _), Instr::Push(f)], instr @ Instr::Prim(Reduce, _))
+            if f.as_function().is_some() =>
+        {
+            replace(instrs, 2, Fused::Reshape, instr);
+        }
+        // Windowed loops
+        ([.., Instr::Prim(Windows, _), Instr::Push(f)], instr @ Instr::Prim(Rows | Reduce, _))
+            if f.as_function().is_some() =>
+        {
+            let kernel = if let Instr::Prim(Rows, _) = instr {
+                Fused::RowsWindows
+            } else {
+                Fused::ReduceWindows
+            };
+            replace(instrs, 2, kernel, instr);
+        }
+        // Sum
+        ([.., Instr::Push(f)], instr @ Instr::Prim(Reduce, _))
+            if (f.as_function()).is_some_and(|f| matches!(f.as_primitive(), Some((Add, _)))) =>
+        {
+            replace(instrs, 1, Fused::Sum, instr);
+        }
+        (_, instr) => instrs.push(instr),
+    }
+}
+
+/// Replace the last `count` instructions and a new one with a fused kernel
+fn replace(instrs: &mut Vec<Instr>, count: usize, kernel: Fused, instr: Instr) {
+    let mut replaced: Vec<Instr> = instrs.drain(instrs.len() - count..).collect();
+    replaced.push(instr);
+    instrs.push(Instr::Fused(kernel, replaced.into()));
+}
+
+/// Push an instruction, folding it into a constant if it is a pure primitive applied to constants
+fn fold(instrs: &mut Vec<Instr>, instr: Instr, env: &mut Uiua) {
+    let Instr::Prim(prim, _) = instr else {
+        instrs.push(instr);
+        return;
+    };
+    let Some(args) = prim.args().map(usize::from).filter(|_| foldable(prim)) else {
+        instrs.push(instr);
+        return;
+    };
+    let Some(start) = instrs.len().checked_sub(args) else {
+        instrs.push(instr);
+        return;
+    };
+    if !instrs[start..]
+        .iter()
+        .all(|instr| instr.as_push().is_some())
+    {
+        instrs.push(instr);
+        return;
+    }
+    let height = env.stack.len();
+    for instr in &instrs[start..] {
+        env.push(instr.as_push().unwrap().clone());
+    }
+    // Errors are left to be reported when the code runs
+    if prim.run(env).is_ok() && env.stack.len() == height + 1 {
+        let val = env.stack.pop().unwrap();
+        instrs.truncate(start);
+        instrs.push(Instr::push(val));
+    } else {
+        env.stack.truncate(height);
+        instrs.push(instr);
+    }
+}
+
+/// Whether a primitive can be evaluated at compile time
+///
+/// Primitives that make large arrays, like `⇡`, or that behave differently
+/// in a fill context, like `↯`, are not folded.
+fn foldable(prim: Primitive) -> bool {
+    use Primitive::*;
+    prim.as_constant().is_some()
+        || matches!(
+            prim,
+            Not | Neg
+                | Abs
+                | Sign
+                | Sqrt
+                | Sin
+                | Cos
+                | Floor
+                | Ceil
+                | Round
+                | Eq
+                | Ne
+                | Lt
+                | Le
+                | Gt
+                | Ge
+                | Add
+                | Sub
+                | Mul
+                | Div
+                | Mod
+                | Pow
+                | Log
+                | Min
+                | Max
+                | Atan
+                | Len
+                | Shape
+                | First
+                | Last
+                | Reverse
+                | Deshape
+                | Transpose
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(code: &str, optimize: bool) -> Result<Vec<Value>, String> {
+        let mut env = Uiua::with_native_sys().with_optimizations(optimize);
+        env.load_str(code).map_err(|e| e.message())?;
+        Ok(env.take_stack())
+    }
+
+    #[test]
+    fn results_match_unoptimized() {
+        for code in [
+            "⊏⍏. [3 1 4 1 5 9 2 6]",
+            "⊏⍖. [3 1 4 1 5 9 2 6]",
+            "⊏⍏. \"hello\"",
+            "⊏⍏. [[3 1] [1 2] [1 1]]",
+            "⊏⍖. [÷0 0 1 ¯∞ 0 ∞]",
+            "⊏⍏. []",
+            "⊏⍏. 5",
+            "⊢⍏ [3 1 4 1 5]",
+            "⊢⍖ [3 1 5 4 1 5]",
+            "⊢⍖ \"abc\"",
+            "⊢⍏ [[1 2] [0 5]]",
+            "⊢⍏ []",
+            "/+ [1 2 3]",
+            "/+ =1 [1 2 1 1]",
+            "/+ >1 ⇡200",
+            "/+ = [[1 2] [2 2]] [[1 1] [2 2]]",
+            "⊏⍏. <5 [3 9 1 7]",
+            "/+ ↯ 1000 255 =. 3",
+            "/+ [[1 2] [3 4]]",
+            "/+ []",
+            "/+ 5",
+            "⇌⇌ [1 2 3]",
+            "∶∶ 1 2",
+            "∘ 5",
+            "○+η 1",
+            "⊢⇌ [1 2 3]",
+            "×2 +1 2",
+            "+1 π",
+            "+ [1 2] [1 2 3]",
+            "⬚0(+ [1 2] [1 2 3])",
+            "[∘] 1",
+            "[[+]] 1 2",
+            "[∶∶] 1 2",
+            "F ← ∘\nF 3",
+            "F ← ⊏⍏.\n≡F [[2 1] [4 3]]",
+            "≡/+◫3 [1 2 3 4 5 6]",
+            "≡/+◫2 [0.1 0.2 0.3 ¯0]",
+            "≡/+◫4 ↯ 100 [255 1 7]",
+            "≡/+◫2 [1e300 1e300 ¯1e300 5]",
+            "≡/↥◫3 [3 1 4 1 5 9 2 6]",
+            "≡/↧◫2 [3 ÷0 0 1 ÷0 0 ÷0 0 ¯∞]",
+            "≡/↥◫2 [0 ¯0 1]",
+            "≡/↧◫1 \"hello\"",
+            "≡/+◫2 [[1 2] [3 4] [5 6]]",
+            "≡(⊂⊢∶⇌)◫2 [1 2 3 4]",
+            "≡□◫3 \"abcde\"",
+            "≡(⎋>3/+.)◫2 [1 2 3 4 5]",
+            "≡/+◫7 [1 2 3]",
+            "≡/+◫0 [1 2 3]",
+            "≡/+◫1.5 [1 2 3]",
+            "≡/+◫[2] [1 2 3]",
+            "≡/+◫2 5",
+            "/+◫2 [1 2 3 4]",
+            "/↥◫2 [[1 5] [4 2] [3 3]]",
+            "/-◫2 [10 4 1]",
+            "/⊂◫2 [1 2 3]",
+            "/(⎋>5⊢.+)◫1 [1 2 3 4]",
+            "/+◫2 [1 2 3 4] 5",
+            "/+◫4 [1 2 3]",
+            "⧻⇡1000000",
+            "△⇡5",
+            "⊢⇡3",
+            "⊢⇡0",
+            "⊢⇡[2 3]",
+            "⊢⇌⇡3",
+            "[⊢⇌⇡3]",
+            "⊢⇌↯3 2",
+            "⊏⇡3 [5 6 7 8]",
+            "⊏⇡3 \"abc\"",
+            "⊏⇡0 [1 2]",
+            "⊏⇡2 [[1 2] [3 4] [5 6]]",
+            "⊏⇡5 [1 2]",
+            "⬚0⊏⇡5 [1 2]",
+            "⊏⇡2 5",
+            "⊏⇡1.5 [1 2]",
+            "/+⇡100",
+            "/+⇡0",
+            "/+⇡1000000",
+            "/×⇡0",
+            "/×⇡1",
+            "/×⇡5",
+            "/↥⇡5",
+            "/↥⇡0",
+            "/↧⇡5",
+            "/↧⇡0",
+            "/-⇡5",
+            "⊞×⇡3 ⇡4",
+            "⊞=⇡3 ⇡3",
+            "⊞-⇡3 [1 2]",
+            "⊞⊂⇡2 [1 2]",
+            "⊞+⇡3 \"ab\"",
+            "⊞(+1+)⇡2 [1 2]",
+            "⊞+⇡¯1 [1 2]",
+            "[⧻⇡3] 1",
+            "⧻↯5 0",
+            "⧻↯[2 3] 1",
+            "△↯[2 3] 1",
+            "△↯4 [1 2]",
+            "⊢↯3 7",
+            "⊢↯[2 2] 7",
+            "/+↯4 2.5",
+            "/+↯1000000 3",
+            "/+↯0 3",
+            "/↥↯3 ¯1",
+            "/↧↯[3] 4",
+            "/-↯3 1",
+            "/+↯¯1 3",
+            "⧻↯[] 1",
+        ] {
+            assert_eq!(run(code, true), run(code, false), "{code}");
+        }
+    }
+
+    #[test]
+    fn idioms_are_optimized() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            "Sort ← ⊏⍏.\nAdd ← ⇌⇌∘+\nConst ← +×2 +1 2\nKeep ← ∘\nRolling ← ≡/+◫\nCount ← ⧻⇡\nLast ← ⊢⇌⇡\nZeros ← /+↯",
+        )
+        .unwrap();
+        let bindings = env.all_bindings_in_scope();
+        let instrs = |name: &str| bindings[name].as_function().unwrap().instrs.clone();
+        assert!(matches!(
+            instrs("Sort").as_slice(),
+            [Instr::Fused(Fused::Sort, _)]
+        ));
+        assert!(matches!(
+            instrs("Add").as_slice(),
+            [Instr::Prim(Primitive::Add, _)]
+        ));
+        assert!(matches!(
+            instrs("Const").as_slice(),
+            [Instr::Push(val), Instr::Prim(Primitive::Add, _)] if **val == Value::from(6.0)
+        ));
+        assert!(matches!(
+            instrs("Rolling").as_slice(),
+            [Instr::Fused(Fused::RowsWindows, _)]
+        ));
+        assert!(matches!(
+            instrs("Count").as_slice(),
+            [Instr::Fused(Fused::Range, _)]
+        ));
+        assert!(matches!(
+            instrs("Last").as_slice(),
+            [Instr::Fused(Fused::Range, _)]
+        ));
+        assert!(matches!(
+            instrs("Zeros").as_slice(),
+            [Instr::Fused(Fused::Reshape, _)]
+        ));
+        // Removing the identity would change the signature
+        assert!(matches!(
+            instrs("Keep").as_slice(),
+            [Instr::Prim(Primitive::Identity, _)]
+        ));
+    }
+
+    #[test]
+    fn fused_instrs_compare_their_contents() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut env = Uiua::with_native_sys();
+        env.load_str("Sums ← ≡/+◫\nMaxes ← ≡/↥◫").unwrap();
+        let bindings = env.all_bindings_in_scope();
+        let instrs = |name: &str| bindings[name].as_function().unwrap().instrs.clone();
+        let hash = |instrs: &[Instr]| {
+            let mut hasher = DefaultHasher::new();
+            instrs.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (sums, maxes) = (instrs("Sums"), instrs("Maxes"));
+        assert!(matches!(
+            sums.as_slice(),
+            [Instr::Fused(Fused::RowsWindows, _)]
+        ));
+        assert_ne!(sums, maxes);
+        assert_ne!(hash(&sums), hash(&maxes));
+    }
+}
@@ -0,0 +1,381 @@
+//! Project manifests and local package dependencies
+//!
+//! A project's `uiua.toml` declares its dependencies:
+//!
+//! ```toml
+//! [package]
+//! name = "my-project"
+//! version = "0.1.0"
+//!
+//! [dependencies]
+//! mathlib = { path = "../mathlib" }
+//! strings = {}
+//! ```
+//!
+//! Dependencies without a `path` are looked up in the project's `vendor` directory.
+//! An import of `"mathlib"` resolves to the dependency's entry file, and an import of
+//! `"mathlib/trig.ua"` resolves to a file inside the dependency's directory.
+//! Other relative imports resolve against the directory of the file that makes them.
+//!
+//! The content hashes of dependencies are recorded in `uiua.lock` so that
+//! changes to shared library files do not go unnoticed.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{UiuaError, UiuaResult};
+
+/// The name of the manifest file
+pub const MANIFEST_FILE: &str = "uiua.toml";
+/// The name of the lockfile
+pub const LOCK_FILE: &str = "uiua.lock";
+/// The directory in which dependencies without a path are found
+pub const VENDOR_DIR: &str = "vendor";
+/// The default entry file of a dependency
+pub const DEFAULT_LIB: &str = "lib.ua";
+
+/// The contents of a `uiua.toml` file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub package: PackageInfo,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+/// The `[package]` section of a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    #[serde(default = "default_version")]
+    pub version: String,
+    /// The file that is imported when the package is used as a dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lib: Option<String>,
+}
+
+fn default_version() -> String {
+    "0.1.0".into()
+}
+
+/// A dependency declaration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Dependency {
+    /// The directory of the dependency, relative to the manifest
+    ///
+    /// If absent, the dependency is looked up in the vendor directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl Manifest {
+    /// Create a manifest for a new package with no dependencies
+    pub fn new(name: impl Into<String>) -> Self {
+        Manifest {
+            package: PackageInfo {
+                name: name.into(),
+                version: default_version(),
+                lib: None,
+            },
+            dependencies: BTreeMap::new(),
+        }
+    }
+    /// Read a manifest from a file
+    pub fn from_file(path: &Path) -> UiuaResult<Self> {
+        let text = fs::read_to_string(path).map_err(|e| load_error(path, e))?;
+        toml::from_str(&text).map_err(|e| load_error(path, e))
+    }
+    /// Serialize the manifest
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("manifest should serialize")
+    }
+    /// Find the manifest that applies to a path by searching it and its ancestors
+    pub fn search(path: &Path) -> Option<PathBuf> {
+        let mut dir = fs::canonicalize(path).ok()?;
+        if dir.is_file() {
+            dir.pop();
+        }
+        loop {
+            let manifest_path = dir.join(MANIFEST_FILE);
+            if manifest_path.is_file() {
+                return Some(manifest_path);
+            }
+            if !dir.pop() {
+                return None;
+            }
+        }
+    }
+}
+
+/// A dependency whose directory has been located
+#[derive(Debug, Clone)]
+pub struct ResolvedDependency {
+    /// The path as written in the lockfile
+    pub path: PathBuf,
+    /// The directory of the dependency
+    pub dir: PathBuf,
+    /// The file that is imported when the dependency is imported by name
+    pub lib: PathBuf,
+}
+
+/// A loaded project manifest with its dependencies resolved
+#[derive(Debug, Clone)]
+pub struct Package {
+    /// The directory containing the manifest
+    pub root: PathBuf,
+    pub manifest: Manifest,
+    pub dependencies: BTreeMap<String, ResolvedDependency>,
+}
+
+/// The contents of a `uiua.lock` file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "dependency")]
+    pub dependencies: Vec<LockedDependency>,
+}
+
+/// A dependency entry in the lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedDependency {
+    pub name: String,
+    pub path: PathBuf,
+    pub hash: String,
+}
+
+impl Package {
+    /// Load a package from its manifest file and locate its dependencies
+    pub fn load(manifest_path: &Path) -> UiuaResult<Self> {
+        let manifest = Manifest::from_file(manifest_path)?;
+        let root = manifest_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let mut dependencies = BTreeMap::new();
+        for (name, dep) in &manifest.dependencies {
+            if name.contains(['/', '\\']) {
+                return Err(load_error(
+                    manifest_path,
+                    format!("Dependency name `{name}` may not contain a path separator"),
+                ));
+            }
+            let path = (dep.path.clone()).unwrap_or_else(|| Path::new(VENDOR_DIR).join(name));
+            let dir = root.join(&path);
+            if !dir.is_dir() {
+                return Err(load_error(
+                    manifest_path,
+                    format!(
+                        "Dependency `{name}` was not found at {}",
+                        dir.to_string_lossy()
+                    ),
+                ));
+            }
+            let dep_manifest = dir.join(MANIFEST_FILE);
+            let lib = if dep_manifest.is_file() {
+                Manifest::from_file(&dep_manifest)?.package.lib
+            } else {
+                None
+            };
+            let lib = dir.join(lib.as_deref().unwrap_or(DEFAULT_LIB));
+            dependencies.insert(name.clone(), ResolvedDependency { path, dir, lib });
+        }
+        Ok(Package {
+            root,
+            manifest,
+            dependencies,
+        })
+    }
+    /// Resolve an import path against the package's dependencies
+    ///
+    /// Returns `None` if the path does not refer to a dependency.
+    pub fn resolve_import(&self, path: &str) -> Option<PathBuf> {
+        let (name, rest) = match path.split_once('/') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        let dep = self.dependencies.get(name)?;
+        Some(match rest {
+            Some(rest) => dep.dir.join(rest),
+            None => dep.lib.clone(),
+        })
+    }
+    /// Compute the lockfile for the current state of the dependencies
+    pub fn lock(&self) -> UiuaResult<Lockfile> {
+        let mut dependencies = Vec::with_capacity(self.dependencies.len());
+        for (name, dep) in &self.dependencies {
+            dependencies.push(LockedDependency {
+                name: name.clone(),
+                path: dep.path.clone(),
+                hash: hash_dir(&dep.dir)?,
+            });
+        }
+        Ok(Lockfile { dependencies })
+    }
+    /// Write the lockfile for the current state of the dependencies
+    pub fn write_lock(&self) -> UiuaResult {
+        let lock_path = self.root.join(LOCK_FILE);
+        let lock = self.lock()?;
+        let text = format!(
+            "# This file is generated by uiua. Do not edit it by hand.\n\n{}",
+            toml::to_string(&lock).expect("lockfile should serialize")
+        );
+        fs::write(&lock_path, text).map_err(|e| UiuaError::Load(lock_path, e.into()))
+    }
+    /// Check that the dependencies match the lockfile
+    ///
+    /// The lockfile is never written here. Only `uiua lock` writes it.
+    /// An error is returned if there is no lockfile for a package with dependencies,
+    /// or if dependencies have been added, removed, or changed since they were locked.
+    pub fn check_lock(&self) -> UiuaResult {
+        let lock_path = self.root.join(LOCK_FILE);
+        if !lock_path.exists() {
+            if self.dependencies.is_empty() {
+                return Ok(());
+            }
+            return Err(load_error(
+                &lock_path,
+                "The dependencies have not been locked. Run `uiua lock` to lock them.",
+            ));
+        }
+        let text = fs::read_to_string(&lock_path).map_err(|e| load_error(&lock_path, e))?;
+        let old: Lockfile = toml::from_str(&text).map_err(|e| load_error(&lock_path, e))?;
+        let new = self.lock()?;
+        if old == new {
+            return Ok(());
+        }
+        for locked in &new.dependencies {
+            let changed = old.dependencies.iter().any(|old| {
+                old.name == locked.name && old.path == locked.path && old.hash != locked.hash
+            });
+            if changed {
+                return Err(load_error(
+                    &lock_path,
+                    format!(
+                        "The contents of dependency `{}` have changed since it was locked. \
+                        Run `uiua lock` to accept the changes.",
+                        locked.name
+                    ),
+                ));
+            }
+        }
+        Err(load_error(
+            &lock_path,
+            "The dependencies have changed since they were locked. \
+            Run `uiua lock` to accept the changes.",
+        ))
+    }
+}
+
+/// Hash the Uiua files in a directory
+fn hash_dir(dir: &Path) -> UiuaResult<String> {
+    fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect(&path, files)?;
+            } else if path.extension().is_some_and(|ext| ext == "ua")
+                || path.file_name().is_some_and(|name| name == MANIFEST_FILE)
+            {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    collect(dir, &mut files).map_err(|e| UiuaError::Load(dir.into(), e.into()))?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let contents = fs::read(&file).map_err(|e| UiuaError::Load(file.clone(), e.into()))?;
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    Ok(format!("sha256:{hash}"))
+}
+
+/// Get the path of the file an import refers to
+///
+/// Paths that name a dependency of the package are resolved against it.
+/// Other relative paths are resolved against the directory of the importing file, if it is known.
+pub fn resolve_import(
+    package: Option<&Package>,
+    importing_file: Option<&Path>,
+    path: &str,
+) -> PathBuf {
+    if let Some(resolved) = package.and_then(|package| package.resolve_import(path)) {
+        return resolved;
+    }
+    match importing_file.and_then(Path::parent) {
+        Some(dir) => dir.join(path),
+        None => path.into(),
+    }
+}
+
+fn load_error(path: &Path, e: impl ToString) -> UiuaError {
+    UiuaError::Load(
+        path.into(),
+        io::Error::new(io::ErrorKind::InvalidData, e.to_string()).into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_and_lock() {
+        let root = std::env::temp_dir().join(format!("uiua-package-{}", std::process::id()));
+        let vendored = root.join(VENDOR_DIR).join("strs");
+        let shared = root.join("shared");
+        fs::create_dir_all(&vendored).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        fs::write(vendored.join(DEFAULT_LIB), "Twice ← ⊂.").unwrap();
+        fs::write(shared.join("main.ua"), "Sq ← ×.").unwrap();
+        fs::write(
+            shared.join(MANIFEST_FILE),
+            "[package]\nname = \"shared\"\nlib = \"main.ua\"",
+        )
+        .unwrap();
+        fs::write(
+            root.join(MANIFEST_FILE),
+            "[package]\nname = \"app\"\n\n[dependencies]\nstrs = {}\nmath = { path = \"shared\" }",
+        )
+        .unwrap();
+
+        let package = Package::load(&root.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(
+            package.resolve_import("strs"),
+            Some(vendored.join(DEFAULT_LIB))
+        );
+        assert_eq!(package.resolve_import("math"), Some(shared.join("main.ua")));
+        assert_eq!(
+            package.resolve_import("math/other.ua"),
+            Some(shared.join("other.ua"))
+        );
+        assert_eq!(package.resolve_import("other.ua"), None);
+
+        assert!(package.check_lock().is_err());
+        assert!(!root.join(LOCK_FILE).exists());
+        package.write_lock().unwrap();
+        package.check_lock().unwrap();
+        fs::write(shared.join("main.ua"), "Sq ← ×..").unwrap();
+        assert!(package.check_lock().is_err());
+        package.write_lock().unwrap();
+        package.check_lock().unwrap();
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -17,6 +17,9 @@ pub enum ParseError {
     InvalidArgCount(String),
     InvalidOutCount(String),
     AmpersandBindingName,
+    TildeBindingName,
+    EmptyImport,
+    ImportItem,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -78,6 +81,11 @@ impl fmt::Display for ParseError {
             ParseError::InvalidArgCount(n) => write!(f, "Invalid argument count `{n}`"),
             ParseError::InvalidOutCount(n) => write!(f, "Invalid output count `{n}`"),
             ParseError::AmpersandBindingName => write!(f, "Binding names may not contain `&`"),
+            ParseError::TildeBindingName => write!(f, "Binding names may not contain `~`"),
+            ParseError::EmptyImport => {
+                write!(f, "An import must name the module or some of its bindings")
+            }
+            ParseError::ImportItem => write!(f, "Only binding names may be imported"),
         }
     }
 }
@@ -193,7 +201,9 @@ impl Parser {
     }
     fn try_item(&mut self, parse_scopes: bool) -> Option<Item> {
         self.try_spaces();
-        Some(if let Some(binding) = self.try_binding() {
+        Some(if let Some(import) = self.try_import() {
+            Item::Import(import)
+        } else if let Some(binding) = self.try_binding() {
             Item::Binding(binding)
         } else if let Some(words) = self.try_words() {
             Item::Words(words)
@@ -221,6 +231,11 @@ impl Parser {
     }
     fn try_binding(&mut self) -> Option<Binding> {
         let start = self.index;
+        // A `~` before the name makes the binding private
+        let private = self.try_exact(Tilde).is_some();
+        if private {
+            self.try_spaces();
+        }
         Some(if let Some(ident) = self.try_ident() {
             if ident.value.contains('&') {
                 self.errors
@@ -231,6 +246,10 @@ impl Parser {
                 self.index = start;
                 return None;
             }
+            if ident.value.contains('~') {
+                self.errors
+                    .push(ident.span.clone().sp(ParseError::TildeBindingName));
+            }
             self.try_spaces();
             let sig = self.try_signature();
             let words = self.try_words().unwrap_or_default();
@@ -261,11 +280,51 @@ impl Parser {
                 name: ident,
                 words,
                 signature: sig,
+                private,
             }
         } else {
+            self.index = start;
             return None;
         })
     }
+    fn try_import(&mut self) -> Option<Import> {
+        let start = self.index;
+        let name = self.try_ident();
+        self.try_spaces();
+        let tilde_span = self.try_exact(Tilde);
+        self.try_spaces();
+        // A `~` followed by anything but a path is a private binding
+        let Some(path) = tilde_span.and_then(|_| self.next_token_map(Token::as_string)) else {
+            self.index = start;
+            return None;
+        };
+        let path = path.map(Into::into);
+        self.try_spaces();
+        let mut items = Vec::new();
+        if self.try_exact(Tilde).is_some() {
+            loop {
+                self.try_spaces();
+                let Some(item) = self.try_ident() else {
+                    break;
+                };
+                items.push(item);
+            }
+        }
+        if name.is_none() && items.is_empty() {
+            self.errors
+                .push(path.span.clone().sp(ParseError::EmptyImport));
+        }
+        // An import takes up the whole line
+        let rest = (self.tokens[self.index..].iter())
+            .take_while(|t| !matches!(t.value, Newline | Comment))
+            .count();
+        if let Some(token) = self.tokens.get(self.index).filter(|_| rest > 0) {
+            self.errors
+                .push(token.span.clone().sp(ParseError::ImportItem));
+        }
+        self.index += rest;
+        Some(Import { name, path, items })
+    }
     fn try_ident(&mut self) -> Option<Sp<Ident>> {
         let span = self.try_exact(Token::Ident)?;
         let s: Ident = span.as_str().into();
@@ -210,28 +210,41 @@ primitive!(
     /// Logical not
     ///
     /// ex: ¬0
+    /// => 1
     /// ex: ¬1
+    /// => 0
     /// ex: ¬[0 1 1 0]
+    /// => [1 0 0 1]
     /// ex: ¬[0 1 2 3]
+    /// => [1 0 ¯1 ¯2]
     ///
     /// This is equivalent to `subtract``flip``1`
     /// ex: ¬7
+    /// => ¯6
     /// ex: ¬[1 2 3 4]
+    /// => [0 ¯1 ¯2 ¯3]
     (1, Not, MonadicPervasive, ("not", '¬')),
     /// Numerical sign (1, ¯1, or 0)
     ///
     /// ex: ± 1
+    /// => 1
     /// ex: ± ¯5
+    /// => ¯1
     /// ex: ± 0
+    /// => 0
     /// ex: ± [¯2 ¯1 0 1 2]
+    /// => [¯1 ¯1 0 1 1]
     (1, Sign, MonadicPervasive, ("sign", '±')),
     /// Negate a number
     ///
     /// Formats from `\``.
     ///
     /// ex: ¯ 1
+    /// => ¯1
     /// ex: ¯ ¯3
+    /// => 3
     /// ex: ¯ [1 2 ¯3]
+    /// => [¯1 ¯2 3]
     (
         1,
         Neg,
@@ -241,15 +254,20 @@ primitive!(
     /// Get the absolute value of a number
     ///
     /// ex: ⌵ ¯1
+    /// => 1
     /// ex: ⌵ 1
+    /// => 1
     ///
     /// The glyph looks like the graph of `|x|`.
     (1, Abs, MonadicPervasive, ("absolute value", '⌵')),
     /// Take the square root of a number
     ///
     /// ex: √4
+    /// => 2
     /// ex: √[1 4 9 16]
+    /// => [1 2 3 4]
     /// ex: √¯1
+    /// => NaN
     (1, Sqrt, MonadicPervasive, ("sqrt", '√')),
     /// Get the sine of a number
     ///
@@ -260,12 +278,15 @@ primitive!(
     ///
     /// You can get an arcsine function with [invert].
     /// ex: ⍘○ 1
+    /// => η
     ///
     /// You can get an arccosine function by [invert]ing the cosine.
     /// ex: ⍘(○+η) 1
+    /// => 0
     ///
     /// You can get a tangent function by [divide]ing the [sine] by the cosine.
     /// ex: ÷○+η∶○. 0
+    /// => 0
     (1, Sin, MonadicPervasive, ("sine", '○')),
     /// Get the cosine of a number
     (1, Cos, MonadicPervasive),
@@ -276,37 +297,55 @@ primitive!(
     /// Round to the nearest integer towards `¯∞`
     ///
     /// ex: ⌊1.5
+    /// => 1
     /// ex: ⌊¯1.5
+    /// => ¯2
     /// ex: ⌊[1.5 ¯1.5 0.5 ¯0.5]
+    /// => [1 ¯2 0 ¯1]
     (1, Floor, MonadicPervasive, ("floor", '⌊')),
     /// Round to the nearest integer towards `∞`
     ///
     /// ex: ⌈1.5
+    /// => 2
     /// ex: ⌈¯1.5
+    /// => ¯1
     /// ex: ⌈[1.5 ¯1.5 0.5 ¯0.5]
+    /// => [2 ¯1 1 0]
     (1, Ceil, MonadicPervasive, ("ceiling", '⌈')),
     /// Round to the nearest integer
     ///
     /// ex: ⁅1.2
+    /// => 1
     /// ex: ⁅¯1.2
+    /// => ¯1
     /// ex: ⁅1.5
+    /// => 2
     /// ex: ⁅[0.1 π 2 9.9 7.5]
+    /// => [0 3 2 10 8]
     (1, Round, MonadicPervasive, ("round", '⁅')),
     /// Compare for equality
     ///
     /// ex: =1 2
+    /// => 0
     /// ex: =5 5
+    /// => 1
     /// ex: =1 [1 2 3]
+    /// => [1 0 0]
     /// ex: = [1 2 2] [1 2 3]
+    /// => [1 1 0]
     (2, Eq, DyadicPervasive, ("equals", AsciiToken::Equal, '=')),
     /// Compare for inequality
     ///
     /// Formats from `!=`.
     ///
     /// ex: ≠1 2
+    /// => 1
     /// ex: ≠5 5
+    /// => 0
     /// ex: ≠1 [1 2 3]
+    /// => [0 1 1]
     /// ex: ≠ [1 2 2] [1 2 3]
+    /// => [0 0 1]
     (
         2,
         Ne,
@@ -318,10 +357,15 @@ primitive!(
     /// The first value is checked to be less than the second.
     /// This is so you can think of `<``x` as a single unit.
     /// ex: <1 2
+    /// => 0
     /// ex: <5 5
+    /// => 0
     /// ex: <7 3
+    /// => 1
     /// ex: <2 [1 2 3]
+    /// => [1 0 0]
     /// ex: < [1 2 2] [1 2 3]
+    /// => [0 0 0]
     (2, Lt, DyadicPervasive, ("less than", '<')),
     /// Compare for less than or equal
     ///
@@ -330,10 +374,15 @@ primitive!(
     /// The first value is checked to be less than or equal to the second.
     /// This is so you can think of `≤``x` as a single unit.
     /// ex: ≤1 2
+    /// => 0
     /// ex: ≤5 5
+    /// => 1
     /// ex: ≤7 3
+    /// => 1
     /// ex: ≤2 [1 2 3]
+    /// => [1 1 0]
     /// ex: ≤ [1 2 2] [1 2 3]
+    /// => [1 1 0]
     (
         2,
         Le,
@@ -345,10 +394,15 @@ primitive!(
     /// The first value is checked to be greater than the second.
     /// This is so you can think of `>``x` as a single unit.
     /// ex: >1 2
+    /// => 1
     /// ex: >5 5
+    /// => 0
     /// ex: >7 3
+    /// => 0
     /// ex: >2 [1 2 3]
+    /// => [0 0 1]
     /// ex: > [1 2 2] [1 2 3]
+    /// => [0 0 1]
     (2, Gt, DyadicPervasive, ("greater than", '>')),
     /// Compare for greater than or equal
     ///
@@ -357,10 +411,15 @@ primitive!(
     /// The first value is checked to be greater than or equal to the second.
     /// This is so you can think of `≥``x` as a single unit.
     /// ex: ≥1 2
+    /// => 1
     /// ex: ≥5 5
+    /// => 1
     /// ex: ≥7 3
+    /// => 0
     /// ex: ≥2 [1 2 3]
+    /// => [0 1 1]
     /// ex: ≥ [1 2 2] [1 2 3]
+    /// => [1 1 1]
     (
         2,
         Ge,
@@ -370,8 +429,11 @@ primitive!(
     /// Add values
     ///
     /// ex: +1 2
+    /// => 3
     /// ex: +1 [2 3 4]
+    /// => [3 4 5]
     /// ex: + [1 2 3] [4 5 6]
+    /// => [5 7 9]
     (2, Add, DyadicPervasive, ("add", '+')),
     /// Subtract values
     ///
@@ -386,12 +448,18 @@ primitive!(
     /// Formats from `*`.
     ///
     /// ex: ×3 5
+    /// => 15
     /// ex: ×2 [1 2 3]
+    /// => [2 4 6]
     /// ex: × [1 2 3] [4 5 6]
+    /// => [4 10 18]
     ///
     /// Uiua does not have dedicated boolean logical operators.
     /// [multiply] can be used as a logical AND.
     /// ex: ×,,≥5∶≤8. [6 2 5 9 6 5 0 4]
+    /// => [1 1 1 0 1 1 1 1]
+    /// => [1 0 1 1 1 1 0 0]
+    /// => [1 0 1 0 1 1 0 0]
     (2, Mul, DyadicPervasive, ("multiply", AsciiToken::Star, '×')),
     /// Divide values
     ///
@@ -400,8 +468,11 @@ primitive!(
     /// The second value is divided by the first.
     /// This is so you can think of `÷``x` as a single unit.
     /// ex: ÷3 12
+    /// => 4
     /// ex: ÷2 [1 2 3]
+    /// => [0.5 1 1.5]
     /// ex: ÷ [1 2 3] [4 5 6]
+    /// => [4 2.5 2]
     (
         2,
         Div,
@@ -413,28 +484,39 @@ primitive!(
     /// The second value is divided by the first, and the remainder is returned.
     /// This is so you can think of `◿``x` as a single unit.
     /// ex: ◿10 27
+    /// => 7
     /// ex: ◿5 [3 7 14]
+    /// => [3 2 4]
     /// ex: ◿ [3 4 5] [10 10 10]
+    /// => [1 2 0]
     (2, Mod, DyadicPervasive, ("modulus", '◿')),
     /// Raise a value to a power
     ///
     /// The second value is raised to the power of the first.
     /// This is so you can think of `ⁿ``x` as a single unit.
     /// ex: ⁿ2 3
+    /// => 9
     /// ex: ⁿ2 [1 2 3]
+    /// => [1 4 9]
     /// ex: ⁿ [1 2 3] [4 5 6]
+    /// => [4 25 216]
     (2, Pow, DyadicPervasive, ("power", 'ⁿ')),
     /// Get the based logarithm of a number
     ///
     /// The first value is the base, and the second value is the power.
     /// ex: ₙ2 8
+    /// => 3
     /// ex: ₙ2 [8 16 32]
+    /// => [3 4 5]
     /// ex: ₙ [2 3 4] [16 27 1024]
+    /// => [4 3 5]
     (2, Log, DyadicPervasive, ("logarithm", 'ₙ')),
     /// Take the minimum of two arrays
     ///
     /// ex: ↧ 3 5
+    /// => 3
     /// ex: ↧ [1 4 2] [3 7 1]
+    /// => [1 4 1]
     ///
     /// Uiua does not have dedicated boolean logical operators.
     /// [minimum] can be used as a logical AND.
@@ -444,25 +526,36 @@ primitive!(
     /// Take the maximum of two arrays
     ///
     /// ex: ↥ 3 5
+    /// => 5
     /// ex: ↥ [1 4 2] [3 7 1]
+    /// => [3 7 2]
     ///
     /// Uiua does not have dedicated boolean logical operators.
     /// [maximum] can be used as a logical OR.
     /// ex: ↥,,≤5∶≥8. [6 2 5 9 6 5 0 4]
+    /// => [0 0 0 1 0 0 0 0]
+    /// => [0 1 1 0 0 1 1 1]
+    /// => [0 1 1 1 0 1 1 1]
     (2, Max, DyadicPervasive, ("maximum", '↥')),
     /// Take the arctangent of two numbers
     ///
     /// This takes a `y` and `x` argument and returns the angle in radians in the range `(-π, π]`.
     /// ex: ∠ 1 0
+    /// => η
     /// ex: ∠ ¯1 0
+    /// => ¯η
     /// ex: ∠ √2 √2
     (2, Atan, DyadicPervasive, ("atangent", '∠')),
     /// Get the number of rows in an array
     ///
     /// ex: ⧻5
+    /// => 1
     /// ex: ⧻[]
+    /// => 0
     /// ex: ⧻1_2_3
+    /// => 3
     /// ex: ⧻[1_2 3_4 5_6]
+    /// => 3
     ///
     /// [length] is equivalent to the [first] of the [shape].
     /// ex:  ⧻[1_2_3 4_5_6]
@@ -471,9 +564,13 @@ primitive!(
     /// Get the dimensions of an array
     ///
     /// ex: △5
+    /// => []
     /// ex: △[]
+    /// => [0]
     /// ex: △1_2_3
+    /// => [3]
     /// ex: △[1_2 3_4 5_6]
+    /// => [3 2]
     ///
     /// It is a triangle`△` because a triangle is a shape.
     (1, Shape, MonadicArray, ("shape", '△')),
@@ -481,7 +578,17 @@ primitive!(
     ///
     /// The rank of the input must be `0` or `1`.
     /// ex: ⇡5
+    /// => [0 1 2 3 4]
     /// ex: ⇡2_3
+    /// => ╭─
+    /// => ╷ 0 0
+    /// => ╷ 0 1
+    /// =>   0 2
+    /// =>
+    /// =>   1 0
+    /// =>   1 1
+    /// =>   1 2
+    /// =>       ╯
     ///
     /// When creating ranges with upper bounds that are rank `1`, [pick]ing the generated range array from an array with the [shape] of the input will yield that array.
     /// ex:     [1_2_3 4_5_6]
@@ -492,7 +599,9 @@ primitive!(
     /// Get the first row of an array
     ///
     /// ex: ⊢1_2_3
+    /// => 1
     /// ex: ⊢[1_2 3_4 5_6]
+    /// => [1 2]
     /// ex! ⊢[]
     /// ex! ⊢1
     (1, First, MonadicArray, ("first", '⊢')),
@@ -501,13 +610,27 @@ primitive!(
     /// Reverse the rows of an array
     ///
     /// ex: ⇌1_2_3_9
+    /// => [9 3 2 1]
     /// ex: ⇌[1_2 3_4 5_6]
+    /// => ╭─
+    /// => ╷ 5 6
+    /// =>   3 4
+    /// =>   1 2
+    /// =>       ╯
     (1, Reverse, MonadicArray, ("reverse", '⇌')),
     /// Make an array 1-dimensional
     ///
     /// ex: ♭5
+    /// => [5]
     /// ex: ♭[1 2 3]
+    /// => [1 2 3]
     /// ex: ♭.[1_2 3_4 5_6]
+    /// => ╭─
+    /// => ╷ 1 2
+    /// =>   3 4
+    /// =>   5 6
+    /// =>       ╯
+    /// => [1 2 3 4 5 6]
     ///
     /// It looks like `♭` because it *flat*tens the array.
     ///
@@ -517,12 +640,35 @@ primitive!(
     ///
     /// The result will always be 1 rank higher than the input.
     /// ex: ⋯27
+    /// => [1 1 0 1 1]
     /// ex: ⋯⇡8
+    /// => ╭─
+    /// => ╷ 0 0 0
+    /// =>   1 0 0
+    /// =>   0 1 0
+    /// =>   1 1 0
+    /// =>   0 0 1
+    /// =>   1 0 1
+    /// =>   0 1 1
+    /// =>   1 1 1
+    /// =>         ╯
     /// ex: ⋯[1_2 3_4 5_6]
+    /// => ╭─
+    /// => ╷ 1 0 0
+    /// => ╷ 0 1 0
+    /// =>
+    /// =>   1 1 0
+    /// =>   0 0 1
+    /// =>
+    /// =>   1 0 1
+    /// =>   0 1 1
+    /// =>         ╯
     ///
     /// [invert][bits] can be used to decode the bits back into numbers.
     /// ex: ⍘⋯ [1 0 1]
+    /// => 5
     /// ex: ⍘⋯ [0 1 1 0 1]
+    /// => 22
     /// ex: ⍘⋯ [[0 1 1]
     ///   :     [1 0 0]
     ///   :     [1 1 0]]
@@ -1387,6 +1533,23 @@ primitive!(
     /// It uses [if] to decide whether to recur.
     /// ex: !(?∘(|1 +↬2-1∶↬2-2.) <2.) 10
     (1(None), Recur, Control, ("recur", '↬')),
+    /// Call a function and cache its outputs
+    ///
+    /// When the function is called again with the same arguments, the cached outputs are pushed instead of calling it.
+    /// ex: F ← memo(×2)
+    ///   : [F 3 F 4 F 3]
+    /// => [6 8 6]
+    ///
+    /// [recur] into a memoized function also uses the cache.
+    /// Here is a recursive fibonacci function that computes each smaller fibonacci number only once.
+    /// ex: F ← memo(|1 ?∘(|1 +↬2-1∶↬2-2.) <2.)
+    ///   : F 10
+    /// => 55
+    ///
+    /// Outputs are cached separately for each [fill] value.
+    /// By default, caches last as long as the interpreter, so later calls with the same arguments are fast too.
+    /// Because of this, [memo] should only be used with functions that have no side effects.
+    ([1], Memo, OtherModifier, "memo"),
     /// Parse a string as a number
     ///
     /// ex: parse "17"
@@ -1480,7 +1643,7 @@ primitive!(
     (0, Infinity, Constant, ("infinity", '∞')),
     /// Debug print the top value on the stack without popping it
     ///
-    /// ex: ~[1 2 3]
+    /// ex: ⸮[1 2 3]
     /// This is useful when you want to inspect an intermediate value.
     /// For example, let's say you are trying to find all the numbers in some range:
     /// ex: [1 5 2 9 11 0 7 12 8 3]
@@ -1488,8 +1651,8 @@ primitive!(
     /// `greater or equal` and `less or equal` each create a partial mask.
     /// To see them, use [trace].
     /// ex: [1 5 2 9 11 0 7 12 8 3]
-    ///   : ▽×~≥5∶~≤10..
-    (1, Trace, Stack, ("trace", '~')),
+    ///   : ▽×⸮≥5∶⸮≤10..
+    (1, Trace, Stack, ("trace", '⸮')),
     /// The inverse of trace
     (1, InvTrace, Stack),
     /// Debug print all the values currently on stack without popping them
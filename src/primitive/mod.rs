@@ -172,6 +172,14 @@ impl Primitive {
     pub fn is_deprecated(&self) -> bool {
         self.deprecation_suggestion().is_some()
     }
+    /// Check if the primitive has no side effects and does not depend on the order it is run in
+    pub fn is_pure(&self) -> bool {
+        use Primitive::*;
+        !matches!(
+            self,
+            Sys(_) | Rand | Tag | Spawn | Wait | Break | Trace | InvTrace | Dump | Memo
+        )
+    }
     pub fn inverse(&self) -> Option<Self> {
         use Primitive::*;
         Some(match self {
@@ -271,10 +279,13 @@ impl Primitive {
             Primitive::Identity => env.touch_array_stack(),
             Primitive::Gap => {
                 let f = env.pop(1)?;
-                let _x = env.pop(2)?;
+                let _x = env.pop_packed(2)?;
                 env.call(f)?;
             }
-            Primitive::Not => env.monadic_env(Value::not)?,
+            Primitive::Not => match env.pop_packed(1)? {
+                Value::Bits(bits) => env.push(Value::Bits(bits.not())),
+                value => env.push(value.not(env)?),
+            },
             Primitive::Neg => env.monadic_env(Value::neg)?,
             Primitive::Abs => env.monadic_env(Value::abs)?,
             Primitive::Sign => env.monadic_env(Value::sign)?,
@@ -286,12 +297,12 @@ impl Primitive {
             Primitive::Floor => env.monadic_env(Value::floor)?,
             Primitive::Ceil => env.monadic_env(Value::ceil)?,
             Primitive::Round => env.monadic_env(Value::round)?,
-            Primitive::Eq => env.dyadic_rr_env(Value::is_eq)?,
-            Primitive::Ne => env.dyadic_rr_env(Value::is_ne)?,
-            Primitive::Lt => env.dyadic_rr_env(Value::is_lt)?,
-            Primitive::Le => env.dyadic_rr_env(Value::is_le)?,
-            Primitive::Gt => env.dyadic_rr_env(Value::is_gt)?,
-            Primitive::Ge => env.dyadic_rr_env(Value::is_ge)?,
+            Primitive::Eq => env.dyadic_rr_env(Value::is_eq_packed)?,
+            Primitive::Ne => env.dyadic_rr_env(Value::is_ne_packed)?,
+            Primitive::Lt => env.dyadic_rr_env(Value::is_lt_packed)?,
+            Primitive::Le => env.dyadic_rr_env(Value::is_le_packed)?,
+            Primitive::Gt => env.dyadic_rr_env(Value::is_gt_packed)?,
+            Primitive::Ge => env.dyadic_rr_env(Value::is_ge_packed)?,
             Primitive::Add => env.dyadic_rr_env(Value::add)?,
             Primitive::Sub => env.dyadic_rr_env(Value::sub)?,
             Primitive::Mul => env.dyadic_rr_env(Value::mul)?,
@@ -306,7 +317,11 @@ impl Primitive {
             Primitive::Join => env.dyadic_oo_env(Value::join)?,
             Primitive::Transpose => env.monadic_mut(Value::transpose)?,
             Primitive::InvTranspose => env.monadic_mut(Value::inv_transpose)?,
-            Primitive::Keep => env.dyadic_ro_env(Value::keep)?,
+            Primitive::Keep => {
+                let counts = env.pop_packed(1)?;
+                let kept = env.pop(2)?;
+                env.push(counts.keep(kept, env)?);
+            }
             Primitive::Unkeep => {
                 let from = env.pop(1)?;
                 let counts = env.pop(2)?;
@@ -421,46 +436,50 @@ impl Primitive {
                 let n = env.pop(1)?.as_nat(env, "Recur expects a natural number")?;
                 env.recur(n)?;
             }
+            Primitive::Memo => {
+                let f = env.pop(FunctionArg(1))?;
+                env.call_memo(f)?;
+            }
             Primitive::Dup => {
-                let x = env.pop(1)?;
+                let x = env.pop_packed(1)?;
                 env.push(x.clone());
                 env.push(x);
             }
             Primitive::Flip => {
-                let a = env.pop(1)?;
-                let b = env.pop(2)?;
+                let a = env.pop_packed(1)?;
+                let b = env.pop_packed(2)?;
                 env.push(a);
                 env.push(b);
             }
             Primitive::Over => {
-                let a = env.pop(1)?;
-                let b = env.pop(2)?;
+                let a = env.pop_packed(1)?;
+                let b = env.pop_packed(2)?;
                 env.push(b.clone());
                 env.push(a);
                 env.push(b);
             }
             Primitive::Pop => {
-                env.pop(1)?;
+                env.pop_packed(1)?;
             }
             Primitive::Roll => {
-                let a = env.pop(1)?;
-                let b = env.pop(2)?;
-                let c = env.pop(3)?;
+                let a = env.pop_packed(1)?;
+                let b = env.pop_packed(2)?;
+                let c = env.pop_packed(3)?;
                 env.push(a);
                 env.push(c);
                 env.push(b);
             }
             Primitive::Unroll => {
-                let a = env.pop(1)?;
-                let b = env.pop(2)?;
-                let c = env.pop(3)?;
+                let a = env.pop_packed(1)?;
+                let b = env.pop_packed(2)?;
+                let c = env.pop_packed(3)?;
                 env.push(b);
                 env.push(a);
                 env.push(c);
             }
             Primitive::Dip => {
                 let f = env.pop(FunctionArg(1))?;
-                let x = env.pop(1)?;
+                let x = env.pop_packed(1)?;
                 env.call(f)?;
                 env.push(x);
             }
@@ -563,7 +582,7 @@ impl Primitive {
             Primitive::Type => {
                 let val = env.pop(1)?;
                 env.push(match val {
-                    Value::Num(_) | Value::Byte(_) => 0,
+                    Value::Num(_) | Value::Byte(_) | Value::Bits(_) => 0,
                     Value::Char(_) => 1,
                     Value::Func(_) => 2,
                 });
@@ -733,6 +752,7 @@ impl PrimDoc {
                 lines.push(PrimDocLine::Example(PrimExample {
                     input: ex.into(),
                     should_error: false,
+                    expected: None,
                     output: OnceLock::new(),
                 }));
             } else if let Some(mut ex) = line.strip_prefix("ex!") {
@@ -743,6 +763,7 @@ impl PrimDoc {
                 lines.push(PrimDocLine::Example(PrimExample {
                     input: ex.into(),
                     should_error: true,
+                    expected: None,
                     output: OnceLock::new(),
                 }));
             } else if let Some(mut ex) = line.strip_prefix(':') {
@@ -756,6 +777,20 @@ impl PrimDoc {
                 } else {
                     lines.push(PrimDocLine::Text(parse_doc_line_fragments(line)));
                 }
+            } else if let Some(mut expected) = line.strip_prefix("=>") {
+                // Expected output of an example
+                if expected.starts_with(' ') {
+                    expected = &expected[1..]
+                }
+                if let Some(PrimDocLine::Example(example)) = lines.last_mut() {
+                    let full = example.expected.get_or_insert_with(String::new);
+                    if !full.is_empty() {
+                        full.push('\n');
+                    }
+                    full.push_str(expected);
+                } else {
+                    lines.push(PrimDocLine::Text(parse_doc_line_fragments(line)));
+                }
             } else if short.is_empty() {
                 // Set short
                 short = parse_doc_line_fragments(line);
@@ -786,6 +821,11 @@ impl PrimDoc {
 pub struct PrimExample {
     input: String,
     should_error: bool,
+    /// The expected output, from `=>` lines after the example
+    ///
+    /// For examples that succeed, this is the shown stack values, bottom first,
+    /// one line per line of output. For examples that fail, it is the error message.
+    expected: Option<String>,
     output: OnceLock<Result<Vec<String>, String>>,
 }
 
@@ -796,27 +836,31 @@ impl PrimExample {
     pub fn should_error(&self) -> bool {
         self.should_error
     }
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
     pub fn should_run(&self) -> bool {
-        !["&sl", "&tcpc"]
+        !["&sl", "&tcpc", "&ffi"]
             .iter()
             .any(|prim| self.input.contains(prim))
     }
     pub fn output(&self) -> &Result<Vec<String>, String> {
-        self.output.get_or_init(|| {
-            let env = &mut Uiua::with_native_sys();
-            match env.load_str(&self.input) {
-                Ok(()) => Ok(env.take_stack().into_iter().map(|val| val.show()).collect()),
-                Err(e) => Err(e
-                    .to_string()
-                    .lines()
-                    .next()
-                    .unwrap_or_default()
-                    .split_once(' ')
-                    .unwrap_or_default()
-                    .1
-                    .into()),
-            }
-        })
+        self.output
+            .get_or_init(|| self.run(&mut Uiua::with_native_sys()))
+    }
+    fn run(&self, env: &mut Uiua) -> Result<Vec<String>, String> {
+        match env.load_str(&self.input) {
+            Ok(()) => Ok(env.take_stack().into_iter().map(|val| val.show()).collect()),
+            Err(e) => Err(e
+                .to_string()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .split_once(' ')
+                .unwrap_or_default()
+                .1
+                .into()),
+        }
     }
 }
 
@@ -961,6 +1005,68 @@ mod tests {
         }
     }
 
+    /// A backend for running examples without doing any real IO
+    ///
+    /// Output is discarded. Channels and threads never leave the process,
+    /// so they are handled by the native backend.
+    struct ExampleBackend;
+
+    impl SysBackend for ExampleBackend {
+        fn any(&self) -> &dyn std::any::Any {
+            self
+        }
+        fn print_str_stdout(&self, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_str_stderr(&self, _: &str) -> Result<(), String> {
+            Ok(())
+        }
+        fn print_str_trace(&self, _: &str) {}
+        fn channel(&self, capacity: Option<usize>) -> Result<(Handle, Handle), String> {
+            NativeSys.channel(capacity)
+        }
+        fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+            NativeSys.channel_send(handle, value)
+        }
+        fn channel_recv(&self, handle: Handle, block: bool) -> Result<Option<Value>, String> {
+            NativeSys.channel_recv(handle, block)
+        }
+        fn close(&self, handle: Handle) -> Result<(), String> {
+            NativeSys.close(handle)
+        }
+        fn spawn(
+            &self,
+            env: Uiua,
+            f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
+        ) -> Result<Handle, String> {
+            NativeSys.spawn(env, f)
+        }
+        fn wait(&self, handle: Handle) -> Result<Vec<Value>, Result<UiuaError, String>> {
+            NativeSys.wait(handle)
+        }
+    }
+
+    fn diff(expected: &str, actual: &str) -> String {
+        let mut diff = String::new();
+        let mut expected = expected.lines();
+        let mut actual = actual.lines();
+        loop {
+            match (expected.next(), actual.next()) {
+                (None, None) => break,
+                (Some(e), Some(a)) if e == a => diff.push_str(&format!("  {e}\n")),
+                (e, a) => {
+                    if let Some(e) = e {
+                        diff.push_str(&format!("- {e}\n"));
+                    }
+                    if let Some(a) = a {
+                        diff.push_str(&format!("+ {a}\n"));
+                    }
+                }
+            }
+        }
+        diff
+    }
+
     #[test]
     fn prim_docs() {
         for prim in Primitive::all() {
@@ -971,10 +1077,11 @@ mod tests {
                             continue;
                         }
                         println!("{prim} example:\n{}", ex.input);
-                        let mut env = Uiua::with_native_sys();
-                        if let Err(e) = env.load_str(&ex.input) {
+                        let mut env = Uiua::with_backend(ExampleBackend);
+                        let output = ex.run(&mut env);
+                        if let Err(e) = &output {
                             if !ex.should_error {
-                                panic!("\nExample failed:\n{}\n{}", ex.input, e.show(true));
+                                panic!("\nExample failed:\n{}\n{e}", ex.input);
                             }
                         } else if let Some(diag) = env.take_diagnostics().into_iter().next() {
                             if !ex.should_error {
@@ -983,6 +1090,22 @@ mod tests {
                         } else if ex.should_error {
                             panic!("Example should have failed: {}", ex.input);
                         }
+                        if let Some(expected) = ex.expected() {
+                            let actual = match output {
+                                Ok(values) => values.join("\n"),
+                                Err(e) => e,
+                            };
+                            // Doc comments can't hold trailing whitespace
+                            let actual: Vec<&str> = actual.lines().map(str::trim_end).collect();
+                            let actual = actual.join("\n");
+                            if actual != expected {
+                                panic!(
+                                    "\nExample output does not match:\n{}\n{}",
+                                    ex.input,
+                                    diff(expected, &actual)
+                                );
+                            }
+                        }
                     }
                 }
             }
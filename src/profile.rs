@@ -63,6 +63,22 @@ Z ← ⊟/- ⁿ2 ∶×2 /×.⇌
 H ← ⁿ∶2÷12
 [×H10.∶ ×H7.∶ ×H4. 220]
 ÷⧻∶ ≡/+ sine ×2×π ⊞× ÷∶ ⇡.&asr.",
+        ),
+        (
+            "ARITHMETIC",
+            "\
+x ← ÷3 +1 +⚂ ⇡1000000
+y ← ⁿ2 ◿7 x
+[/↥ x /↧ y /+ <y x /+ =⌊x ⌊y /+ ×x +y -1 x]",
+        ),
+        (
+            "LOOPS",
+            "\
+x ← +⚂ ⇡1000000
+;⍥(×1.0001 +0.5)20 x
+;⍥(+.)20 <0.5 ◿1 x
+;⍥(;/↥.)50 x
+;⍥(;/↧.)50 x",
         ),
         (
             "LOGO",
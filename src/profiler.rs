@@ -0,0 +1,169 @@
+//! Measuring where time is spent in running code
+//!
+//! Profiling is enabled with [`Uiua::with_profiling`](crate::Uiua::with_profiling).
+//! Time is attributed to the call stack that was running, made up of the bindings,
+//! anonymous functions, and primitives that were called to get there.
+//! Time spent on other threads is attributed to the code that waits for them.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    time::Duration,
+};
+
+use crate::{function::FunctionId, primitive::Primitive};
+
+/// An interned call stack
+pub(crate) type StackId = usize;
+
+/// Raw profiling data collected while running
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProfileData {
+    /// The names of the interned call stacks, with frames separated by `;`
+    names: Vec<String>,
+    /// The call stacks that end in a function, keyed by the function and the caller's stack
+    function_stacks: HashMap<FunctionId, HashMap<Option<StackId>, StackId>>,
+    /// The call stacks that end in a primitive, keyed by the primitive and the caller's stack
+    prim_stacks: HashMap<(Option<StackId>, Primitive), StackId>,
+    /// The milliseconds spent in each call stack
+    times: HashMap<StackId, f64>,
+    /// The call stack that is currently running
+    pub current: Option<StackId>,
+    /// The time the current call stack started running
+    start: f64,
+}
+
+impl ProfileData {
+    /// Attribute the time since the last mark to the current call stack and switch to another
+    pub fn mark(&mut self, next: Option<StackId>) {
+        let now = instant::now();
+        if let Some(stack) = self.current {
+            *self.times.entry(stack).or_default() += now - self.start;
+        }
+        self.start = now;
+        self.current = next;
+    }
+    /// Get the call stack made by calling a function from another stack
+    pub fn function_stack(&mut self, caller: Option<StackId>, id: &FunctionId) -> StackId {
+        if let Some(&stack) = self.function_stacks.get(id).and_then(|c| c.get(&caller)) {
+            return stack;
+        }
+        let stack = self.intern(caller, frame_name(id));
+        (self.function_stacks.entry(id.clone()).or_default()).insert(caller, stack);
+        stack
+    }
+    /// Get the call stack made by calling a primitive from another stack
+    pub fn prim_stack(&mut self, caller: Option<StackId>, prim: Primitive) -> StackId {
+        if let Some(&stack) = self.prim_stacks.get(&(caller, prim)) {
+            return stack;
+        }
+        let stack = self.intern(caller, prim_name(prim));
+        self.prim_stacks.insert((caller, prim), stack);
+        stack
+    }
+    fn intern(&mut self, caller: Option<StackId>, frame: String) -> StackId {
+        let name = match caller {
+            Some(caller) => format!("{};{frame}", self.names[caller]),
+            None => frame,
+        };
+        self.names.push(name);
+        self.names.len() - 1
+    }
+}
+
+/// The name of a call stack frame
+pub(crate) fn frame_name(id: &FunctionId) -> String {
+    match id {
+        FunctionId::Named(name) => name.to_string(),
+        FunctionId::Anonymous(span) => format!("fn {}:{}", span.start.line, span.start.col),
+        FunctionId::Primitive(prim) => prim_name(*prim),
+        FunctionId::Constant => "constant".into(),
+        FunctionId::Main => "main".into(),
+        FunctionId::Composed(ids) => ids.iter().map(frame_name).collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// The name of a primitive in a call stack
+///
+/// Glyphs are not used because some of them, like `;`, have meaning in folded stacks.
+pub(crate) fn prim_name(prim: Primitive) -> String {
+    match prim.name() {
+        Some(name) => name.into(),
+        None => format!("{prim:?}").to_lowercase(),
+    }
+}
+
+/// Time spent in a single binding or primitive
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    pub name: String,
+    /// The time spent in the entry itself
+    pub self_time: Duration,
+    /// The time spent in the entry and everything it called
+    pub total_time: Duration,
+}
+
+/// A summary of where time was spent
+#[derive(Debug, Clone, Default)]
+pub struct ProfileReport {
+    /// The time spent in each call stack, with frames separated by `;`
+    pub stacks: BTreeMap<String, Duration>,
+}
+
+impl ProfileReport {
+    pub(crate) fn new(data: &ProfileData) -> Self {
+        let stacks = (data.times.iter())
+            .map(|(&stack, &ms)| {
+                let name = data.names[stack].clone();
+                (name, Duration::from_secs_f64(ms / 1000.0))
+            })
+            .collect();
+        ProfileReport { stacks }
+    }
+    /// The total time that was measured
+    pub fn total(&self) -> Duration {
+        self.stacks.values().sum()
+    }
+    /// Generate folded stacks, with times in microseconds
+    ///
+    /// This is the format read by tools like `flamegraph.pl` and `inferno-flamegraph`.
+    pub fn to_folded(&self) -> String {
+        let mut folded = String::new();
+        for (stack, time) in &self.stacks {
+            let micros = time.as_micros();
+            if micros > 0 {
+                _ = writeln!(folded, "{stack} {micros}");
+            }
+        }
+        folded
+    }
+    /// Get the `n` entries that took the most time themselves
+    pub fn top(&self, n: usize) -> Vec<ProfileEntry> {
+        let mut entries: HashMap<&str, ProfileEntry> = HashMap::new();
+        for (stack, &time) in &self.stacks {
+            let frames: Vec<&str> = stack.split(';').collect();
+            let mut seen = HashSet::new();
+            for &frame in &frames {
+                if seen.insert(frame) {
+                    let entry = entries.entry(frame).or_insert_with(|| ProfileEntry {
+                        name: frame.into(),
+                        self_time: Duration::ZERO,
+                        total_time: Duration::ZERO,
+                    });
+                    entry.total_time += time;
+                }
+            }
+            if let Some(&last) = frames.last() {
+                entries.get_mut(last).unwrap().self_time += time;
+            }
+        }
+        let mut entries: Vec<ProfileEntry> = entries.into_values().collect();
+        entries.sort_by(|a, b| {
+            (b.self_time.cmp(&a.self_time))
+                .then(b.total_time.cmp(&a.total_time))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        entries.truncate(n);
+        entries
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs,
     hash::Hash,
     mem::take,
@@ -14,10 +14,16 @@ use parking_lot::Mutex;
 
 use crate::{
     array::Array,
+    bench::{Bench, BenchOptions, BenchResult, BenchStats},
+    check::instrs_signature,
+    coverage::{CoverageData, CoverageReport},
     function::*,
     lex::Span,
+    package::{self, Package},
     parse::parse,
     primitive::{Primitive, CONSTANTS},
+    profiler::{ProfileData, ProfileReport, StackId},
+    testing::TestResult,
     value::Value,
     Diagnostic, DiagnosticKind, Handle, Ident, NativeSys, SysBackend, TraceFrame, UiuaError,
     UiuaResult,
@@ -35,9 +41,9 @@ pub struct Uiua {
     /// The thread's stack
     pub(crate) stack: Vec<Value>,
     /// The thread's temp stack for inlining
-    inline_stack: Vec<Value>,
+    pub(crate) inline_stack: Vec<Value>,
     /// The thread's temp stack for unders
-    under_stack: Vec<Value>,
+    pub(crate) under_stack: Vec<Value>,
     /// The current scope
     pub(crate) scope: Scope,
     /// Ancestor scopes of the current one
@@ -50,8 +56,8 @@ pub struct Uiua {
     execution_start: f64,
     /// The paths of files currently being imported (used to detect import cycles)
     current_imports: Arc<Mutex<HashSet<PathBuf>>>,
-    /// The stacks of imported files
-    imports: Arc<Mutex<HashMap<PathBuf, Vec<Value>>>>,
+    /// The stacks and exported bindings of imported files
+    imports: Arc<Mutex<HashMap<PathBuf, Module>>>,
     /// Accumulated diagnostics
     pub(crate) diagnostics: BTreeSet<Diagnostic>,
     /// Print diagnostics as they are encountered
@@ -62,6 +68,44 @@ pub struct Uiua {
     cli_file_path: PathBuf,
     /// The system backend
     pub(crate) backend: Arc<dyn SysBackend>,
+    /// The maximum number of threads to split array operations across
+    max_threads: usize,
+    /// The package whose dependencies imports are resolved against
+    package: Option<Arc<Package>>,
+    /// Sources of files that can be imported without reading them from the backend
+    pub(crate) embedded_files: Arc<BTreeMap<String, String>>,
+    /// Only tests whose names contain this are run
+    pub(crate) test_filter: Option<String>,
+    /// The results of tests that have been run
+    pub(crate) test_results: Vec<TestResult>,
+    /// Coverage data, if coverage is being tracked
+    pub(crate) coverage: Option<Arc<Mutex<CoverageData>>>,
+    /// Profiling data, if time is being measured
+    profile: Option<ProfileData>,
+    /// The code of benchmarks, if they are being collected
+    pub(crate) benches: Option<Vec<Bench>>,
+    /// Whether compiled instructions are optimized
+    pub(crate) optimize: bool,
+    /// Whether optimization evaluates constant expressions at compile time
+    pub(crate) fold: bool,
+    /// The cached outputs of memoized functions
+    memo: Arc<Mutex<MemoCache>>,
+    /// The maximum number of outputs cached for each memoized function
+    memo_limit: usize,
+    /// How long memoized outputs are cached
+    memo_scope: MemoScope,
+}
+
+/// The outputs of each memoized function, keyed by its arguments
+type MemoCache = HashMap<Arc<Function>, HashMap<MemoKey, Vec<Value>>>;
+
+/// The arguments of a memoized call and the fill values that were set for it
+#[derive(PartialEq, Eq, Hash)]
+struct MemoKey {
+    args: Vec<Value>,
+    num_fill: Option<u64>,
+    char_fill: Option<char>,
+    func_fill: Option<Arc<Function>>,
 }
 
 #[derive(Clone)]
@@ -72,6 +116,10 @@ pub struct Scope {
     call: Vec<StackFrame>,
     /// Map local names to global indices
     pub names: HashMap<Ident, usize>,
+    /// Map the names of public bindings to global indices
+    pub exports: HashMap<Ident, usize>,
+    /// The names of imported modules
+    pub modules: HashSet<Ident>,
     /// Whether this scope is local
     pub local: bool,
     /// The current fill values
@@ -91,14 +139,27 @@ impl Default for Scope {
                 call_span: 0,
                 pc: 0,
                 spans: Vec::new(),
+                memo: false,
+                profile: None,
             }],
             names: HashMap::new(),
+            exports: HashMap::new(),
+            modules: HashSet::new(),
             local: false,
             fills: Fills::default(),
         }
     }
 }
 
+/// The result of importing a file
+#[derive(Clone)]
+struct Module {
+    /// The values left on the stack
+    stack: Vec<Value>,
+    /// The public bindings
+    exports: HashMap<Ident, usize>,
+}
+
 #[derive(Default, Clone)]
 struct Fills {
     nums: Vec<f64>,
@@ -116,6 +177,22 @@ struct StackFrame {
     pc: usize,
     /// Additional spans for error reporting
     spans: Vec<(usize, Option<Primitive>)>,
+    /// Whether the function's outputs are cached, so that recursion into it uses the cache
+    memo: bool,
+    /// The interned call stack of the function, when profiling
+    profile: Option<StackId>,
+}
+
+/// The interned call stack of a frame and the primitives it is running, for profiling
+fn frame_stack(profile: &mut ProfileData, frame: &StackFrame) -> StackId {
+    let mut stack =
+        (frame.profile).unwrap_or_else(|| profile.function_stack(None, &frame.function.id));
+    for &(_, prim) in &frame.spans {
+        if let Some(prim) = prim {
+            stack = profile.prim_stack(Some(stack), prim);
+        }
+    }
+    stack
 }
 
 impl Default for Uiua {
@@ -124,18 +201,41 @@ impl Default for Uiua {
     }
 }
 
+/// How long the outputs of functions called with [`Primitive::Memo`] are cached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum MemoScope {
+    /// Cache outputs for as long as the interpreter lives
+    #[default]
+    Global,
+    /// Cache outputs only during the outermost call of each memoized function
+    ///
+    /// Calls that [`recur`](Primitive::Recur) into the function share the cache,
+    /// but the next call from outside the function starts with an empty one.
+    Call,
+}
+
 /// A mode that affects how non-binding lines are run
 ///
-/// Regardless of the mode, lines with a call to `import` will always be run
+/// Regardless of the mode, lines and bindings with a call to `import` will always be run
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum RunMode {
     /// Only run lines outside of test blocks
     #[default]
     Normal,
     /// Only run non-binding lines inside of test blocks
+    ///
+    /// Each test block is a separate test. Its result is recorded,
+    /// and later tests are run even if it fails.
     Test,
     /// Run everything
     All,
+    /// Compile everything but only run imports
+    ///
+    /// Constant bindings and bindings that call `import` or [`use`](crate::primitive::Primitive::Use)
+    /// are evaluated, so that imported bindings can be checked.
+    /// Other bindings that would be evaluated immediately are instead bound to functions
+    /// that would compute them, so that their signatures can still be checked.
+    Check,
 }
 
 impl FromStr for RunMode {
@@ -145,6 +245,7 @@ impl FromStr for RunMode {
             "normal" => Ok(RunMode::Normal),
             "test" => Ok(RunMode::Test),
             "all" => Ok(RunMode::All),
+            "check" => Ok(RunMode::Check),
             _ => Err(format!("unknown run mode `{}`", s)),
         }
     }
@@ -178,6 +279,19 @@ impl Uiua {
             cli_file_path: PathBuf::new(),
             execution_limit: None,
             execution_start: 0.0,
+            max_threads: num_cpus::get(),
+            package: None,
+            embedded_files: Arc::new(BTreeMap::new()),
+            test_filter: None,
+            test_results: Vec::new(),
+            coverage: None,
+            profile: None,
+            benches: None,
+            optimize: true,
+            fold: true,
+            memo: Arc::new(Mutex::new(HashMap::new())),
+            memo_limit: 1 << 16,
+            memo_scope: MemoScope::Global,
         }
     }
     /// Create a new Uiua runtime with a custom IO backend
@@ -231,6 +345,146 @@ impl Uiua {
     pub fn file_path(&self) -> &Path {
         self.cli_file_path.as_path()
     }
+    /// Resolve imports against the dependencies of a package
+    pub fn with_package(mut self, package: Package) -> Self {
+        self.package = Some(Arc::new(package));
+        self
+    }
+    /// Make files available to import without reading them from the backend
+    ///
+    /// The files are keyed by the paths their imports resolve to.
+    pub fn with_embedded_files(mut self, files: BTreeMap<String, String>) -> Self {
+        self.embedded_files = Arc::new(files);
+        self
+    }
+    /// Only run tests whose names contain a string
+    pub fn with_test_filter(mut self, filter: impl Into<String>) -> Self {
+        self.test_filter = Some(filter.into());
+        self
+    }
+    /// Take the results of the tests that have been run
+    pub fn take_test_results(&mut self) -> Vec<TestResult> {
+        take(&mut self.test_results)
+    }
+    /// Track which code is executed
+    ///
+    /// Constant expressions are not folded while tracking,
+    /// so that the lines they are on are counted when they run.
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = Some(Default::default());
+        self.fold = false;
+        self
+    }
+    /// Get a report of which code has been executed
+    ///
+    /// Returns `None` if coverage is not being tracked.
+    pub fn coverage(&self) -> Option<CoverageReport> {
+        let data = self.coverage.as_ref()?.lock();
+        Some(CoverageReport::new(&data, &self.spans.lock()))
+    }
+    /// Measure how much time is spent in each binding and primitive
+    pub fn with_profiling(mut self) -> Self {
+        self.profile = Some(Default::default());
+        self
+    }
+    /// Get a report of where time has been spent
+    ///
+    /// Returns `None` if profiling is not enabled.
+    pub fn profile(&self) -> Option<ProfileReport> {
+        self.profile.as_ref().map(ProfileReport::new)
+    }
+    /// Set whether compiled instructions are optimized
+    ///
+    /// Optimizations are enabled by default. They never change the result of a program.
+    pub fn with_optimizations(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+    /// Keep the code of benchmarks so that they can be run with [`Uiua::run_benches`]
+    pub fn with_benches(mut self) -> Self {
+        self.benches = Some(Vec::new());
+        self
+    }
+    /// Run the benchmarks that have been loaded
+    ///
+    /// Each one is run some number of times without being measured first.
+    pub fn run_benches(&mut self, options: &BenchOptions) -> UiuaResult<Vec<BenchResult>> {
+        let benches = self.benches.clone().unwrap_or_default();
+        let mut results = Vec::new();
+        for bench in benches {
+            if (options.filter.as_ref()).is_some_and(|filter| !bench.name.contains(filter.as_str()))
+            {
+                continue;
+            }
+            match instrs_signature(&bench.instrs) {
+                Ok(sig) if sig.args == 0 => {}
+                Ok(sig) => {
+                    return Err(UiuaError::Run(Span::Code(bench.span).sp(format!(
+                        "Benchmark `{}` takes {} argument{}, but benchmarks cannot take arguments",
+                        bench.name,
+                        sig.args,
+                        if sig.args == 1 { "" } else { "s" }
+                    ))))
+                }
+                Err(e) => {
+                    return Err(UiuaError::Run(Span::Code(bench.span).sp(format!(
+                        "Cannot infer signature of benchmark `{}`: {e}",
+                        bench.name
+                    ))))
+                }
+            }
+            let mut samples = Vec::with_capacity(options.runs);
+            for i in 0..options.warmup + options.runs {
+                let height = self.stack.len();
+                let start = instant::now();
+                self.exec_global_instrs(bench.instrs.clone())?;
+                let ms = (instant::now() - start).max(0.0);
+                self.stack.truncate(height);
+                if i >= options.warmup {
+                    samples.push(Duration::from_secs_f64(ms / 1000.0));
+                }
+            }
+            results.push(BenchResult {
+                name: bench.name,
+                span: bench.span,
+                stats: BenchStats::new(&samples),
+                samples,
+            });
+        }
+        Ok(results)
+    }
+    /// Get the path of the file an import refers to
+    ///
+    /// `importing_file` is the path of the file the import is written in.
+    pub(crate) fn resolve_import(&self, importing_file: Option<&Path>, path: &str) -> PathBuf {
+        package::resolve_import(self.package.as_deref(), importing_file, path)
+    }
+    /// Set the maximum number of threads that large array operations may be split across
+    ///
+    /// Default is the number of logical CPUs. A value of 1 disables parallel execution.
+    pub fn with_max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = max_threads.max(1);
+        self
+    }
+    /// Get the maximum number of threads that large array operations may be split across
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+    /// Set the maximum number of outputs cached for each function called with [`Primitive::Memo`]
+    ///
+    /// When a function's cache is full, it is cleared before new outputs are cached.
+    /// Default is 65536. A value of 0 disables caching.
+    pub fn with_memo_limit(mut self, memo_limit: usize) -> Self {
+        self.memo_limit = memo_limit;
+        self
+    }
+    /// Set how long the outputs of functions called with [`Primitive::Memo`] are cached
+    ///
+    /// Default is [`MemoScope::Global`].
+    pub fn with_memo_scope(mut self, memo_scope: MemoScope) -> Self {
+        self.memo_scope = memo_scope;
+        self
+    }
     /// Load a Uiua file from a path
     pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> UiuaResult {
         let path = path.as_ref();
@@ -259,9 +513,13 @@ impl Uiua {
         self.higher_scopes.push(take(&mut self.scope));
         self.scope.local = local;
         let start_height = self.stack.len();
-        f(self)?;
+        let res = f(self);
         let end_height = self.stack.len();
         self.scope = self.higher_scopes.pop().unwrap();
+        if let Err(e) = res {
+            self.stack.truncate(start_height);
+            return Err(e);
+        }
         Ok(self.stack.split_off(start_height.min(end_height)))
     }
     fn load_impl(&mut self, input: &str, path: Option<&Path>) -> UiuaResult {
@@ -325,6 +583,17 @@ code:
         }
     }
     pub(crate) fn import(&mut self, input: &str, path: &Path) -> UiuaResult {
+        self.load_module(input, path)?;
+        self.stack
+            .extend(self.imports.lock()[path].stack.iter().cloned());
+        Ok(())
+    }
+    /// Load a file as a module if it has not been already, and get its exported bindings
+    pub(crate) fn load_module(
+        &mut self,
+        input: &str,
+        path: &Path,
+    ) -> UiuaResult<HashMap<Ident, usize>> {
         if self.current_imports.lock().contains(path) {
             return Err(self.error(format!(
                 "Cycle detected importing {}",
@@ -332,11 +601,15 @@ code:
             )));
         }
         if !self.imports.lock().contains_key(path) {
-            let import = self.in_scope(false, |env| env.load_str_path(input, path).map(drop))?;
-            self.imports.lock().insert(path.into(), import);
+            let mut exports = HashMap::new();
+            let stack = self.in_scope(false, |env| {
+                env.load_str_path(input, path)?;
+                exports = take(&mut env.scope.exports);
+                Ok(())
+            })?;
+            (self.imports.lock()).insert(path.into(), Module { stack, exports });
         }
-        self.stack.extend(self.imports.lock()[path].iter().cloned());
-        Ok(())
+        Ok(self.imports.lock()[path].exports.clone())
     }
     pub(crate) fn exec_global_instrs(&mut self, instrs: Vec<Instr>) -> UiuaResult {
         let func = Function::new(FunctionId::Main, instrs, Signature::new(0, 0));
@@ -345,9 +618,31 @@ code:
             call_span: 0,
             spans: Vec::new(),
             pc: 0,
+            memo: false,
+            profile: None,
         })
     }
     fn exec(&mut self, frame: StackFrame) -> UiuaResult {
+        let Some(profile) = &self.profile else {
+            return self.exec_frame(frame);
+        };
+        // Time after the frame returns belongs to the caller
+        let caller = profile.current;
+        let res = self.exec_frame(frame);
+        if let Some(profile) = &mut self.profile {
+            profile.mark(caller);
+        }
+        res
+    }
+    fn exec_frame(&mut self, mut frame: StackFrame) -> UiuaResult {
+        if let Some(profile) = &mut self.profile {
+            let caller = (self.scope.call.last()).map(|caller| frame_stack(profile, caller));
+            frame.profile = match caller {
+                // Running top-level code pushes a main frame on top of the scope's own
+                Some(caller) if frame.function.id == FunctionId::Main => Some(caller),
+                _ => Some(profile.function_stack(caller, &frame.function.id)),
+            };
+        }
         let ret_height = self.scope.call.len();
         self.scope.call.push(frame);
         while self.scope.call.len() > ret_height {
@@ -356,6 +651,18 @@ code:
                 self.scope.call.pop();
                 continue;
             };
+            if let Some(coverage) = &self.coverage {
+                if let Some(span) = instr.span() {
+                    *coverage.lock().hits.entry(span).or_default() += 1;
+                }
+            }
+            if let Some(profile) = &mut self.profile {
+                let mut stack = frame_stack(profile, frame);
+                if let &Instr::Prim(prim, _) = instr {
+                    stack = profile.prim_stack(Some(stack), prim);
+                }
+                profile.mark(Some(stack));
+            }
             // Uncomment to debug
             // if !self.scope.array.is_empty() {
             //     print!("array: ");
@@ -381,7 +688,7 @@ code:
                 &Instr::EndArray { span, constant } => (|| {
                     let start = self.scope.array.pop().unwrap();
                     self.push_span(span, None);
-                    let values = self.stack.drain(start..).rev();
+                    let values = self.stack.drain(start..).rev().map(Value::unpack);
                     let values: Vec<Value> = if constant {
                         values
                             .map(Function::constant)
@@ -410,6 +717,10 @@ code:
                     .pop("called function")
                     .and_then(|f| self.call_with_span(f, span)),
                 Instr::Dynamic(df) => df.f.clone()(self),
+                Instr::Fused(fused, instrs) => {
+                    let (fused, instrs) = (*fused, instrs.clone());
+                    fused.run(&instrs, self)
+                }
                 &Instr::PushTemp { count, span, kind } => (|| {
                     self.push_span(span, None);
                     for _ in 0..count {
@@ -520,6 +831,8 @@ code:
                     call_span,
                     spans: Vec::new(),
                     pc: 0,
+                    memo: false,
+                    profile: None,
                 })
             }
             value => {
@@ -546,8 +859,71 @@ code:
                 self.scope.call.len()
             )));
         }
-        let f = self.scope.call[self.scope.call.len() - n].function.clone();
-        self.call(f)
+        let frame = &self.scope.call[self.scope.call.len() - n];
+        let f = frame.function.clone();
+        if frame.memo {
+            self.call_memoized(f)
+        } else {
+            self.call(f)
+        }
+    }
+    /// Call a function, reusing its outputs if it has been called with the same arguments before
+    pub(crate) fn call_memo(&mut self, f: Value) -> UiuaResult {
+        match f {
+            Value::Func(f) if f.shape.is_empty() => self.call_memoized(f.into_scalar().unwrap()),
+            value => {
+                self.push(value);
+                Ok(())
+            }
+        }
+    }
+    fn call_memoized(&mut self, f: Arc<Function>) -> UiuaResult {
+        let sig = f.signature();
+        let args = self.clone_stack_top(sig.args);
+        // Fills can change a function's outputs, so they are part of the key
+        let key = MemoKey {
+            args,
+            num_fill: self.num_fill().map(f64::to_bits),
+            char_fill: self.char_fill(),
+            func_fill: self.func_fill(),
+        };
+        let cached = (self.memo.lock().get(&f))
+            .and_then(|outputs| outputs.get(&key))
+            .cloned();
+        if let Some(outputs) = cached {
+            self.truncate_stack(self.stack_size() - sig.args);
+            self.stack.extend(outputs);
+            return Ok(());
+        }
+        let call_span = self.span_index();
+        let res = self.exec(StackFrame {
+            function: f.clone(),
+            call_span,
+            spans: Vec::new(),
+            pc: 0,
+            memo: true,
+            profile: None,
+        });
+        if self.memo_scope == MemoScope::Call
+            && !(self.scope.call.iter()).any(|frame| frame.memo && Arc::ptr_eq(&frame.function, &f))
+        {
+            // The outermost call's cache is not used after it returns
+            self.memo.lock().remove(&f);
+            return res;
+        }
+        res?;
+        if self.memo_limit == 0 || key.args.len() < sig.args {
+            return Ok(());
+        }
+        let outputs = self.clone_stack_top(sig.outputs);
+        let mut memo = self.memo.lock();
+        let cache = memo.entry(f).or_default();
+        // Clearing a full cache keeps memory bounded without tracking usage
+        if cache.len() >= self.memo_limit {
+            cache.clear();
+        }
+        cache.insert(key, outputs);
+        Ok(())
     }
     pub fn call_catch_break(&mut self, f: Value) -> UiuaResult<bool> {
         match self.call(f) {
@@ -604,7 +980,15 @@ code:
             .insert(Diagnostic::new(message.into(), self.span(), kind));
     }
     /// Pop a value from the stack
+    ///
+    /// Bit-packed booleans are unpacked into bytes.
     pub fn pop(&mut self, arg: impl StackArg) -> UiuaResult<Value> {
+        self.pop_packed(arg).map(Value::unpack)
+    }
+    /// Pop a value from the stack, leaving bit-packed booleans packed
+    ///
+    /// This is for primitives that either handle [`Value::Bits`] or only move values around.
+    pub(crate) fn pop_packed(&mut self, arg: impl StackArg) -> UiuaResult<Value> {
         let res = self.stack.pop().ok_or_else(|| {
             self.error(format!(
                 "Stack was empty when evaluating {}",
@@ -629,6 +1013,9 @@ code:
     /// Take the entire stack
     pub fn take_stack(&mut self) -> Vec<Value> {
         take(&mut self.stack)
+            .into_iter()
+            .map(Value::unpack)
+            .collect()
     }
     /// Get the values for all bindings in the current scope
     pub fn all_bindings_in_scope(&self) -> HashMap<Ident, Value> {
@@ -651,7 +1038,10 @@ code:
         take(&mut self.diagnostics)
     }
     pub fn clone_stack_top(&self, n: usize) -> Vec<Value> {
-        self.stack.iter().rev().take(n).rev().cloned().collect()
+        (self.stack.iter().rev().take(n).rev())
+            .cloned()
+            .map(Value::unpack)
+            .collect()
     }
     pub(crate) fn monadic_ref<V: Into<Value>>(&mut self, f: fn(&Value) -> V) -> UiuaResult {
         let value = self.pop(1)?;
@@ -764,6 +1154,8 @@ code:
                     set = true;
                 }
             }
+            // Bit arrays are never scalars
+            Value::Bits(_) => {}
         }
         if !set {
             return Err(self.error(format!(
@@ -773,7 +1165,7 @@ code:
         }
         let res = in_ctx(self);
         match fill {
-            Value::Num(_) | Value::Byte(_) => {
+            Value::Num(_) | Value::Byte(_) | Value::Bits(_) => {
                 self.scope.fills.nums.pop();
             }
             Value::Char(_) => {
@@ -786,26 +1178,13 @@ code:
         res
     }
     /// Spawn a thread
-    pub(crate) fn spawn(
-        &mut self,
-        capture_count: usize,
-        f: impl FnOnce(&mut Self) -> UiuaResult + Send + 'static,
-    ) -> UiuaResult<Value> {
-        if self.stack.len() < capture_count {
-            return Err(self.error(format!(
-                "Excepted at least {} value(s) on the stack, but there are {}",
-                capture_count,
-                self.stack.len()
-            )))?;
-        }
-        let env = Uiua {
+    /// Create an environment with an empty stack for running code on another thread
+    pub(crate) fn thread_env(&self) -> Self {
+        Uiua {
             new_functions: Vec::new(),
             globals: self.globals.clone(),
             spans: self.spans.clone(),
-            stack: self
-                .stack
-                .drain(self.stack.len() - capture_count..)
-                .collect(),
+            stack: Vec::new(),
             inline_stack: Vec::new(),
             under_stack: Vec::new(),
             scope: self.scope.clone(),
@@ -820,7 +1199,38 @@ code:
             backend: self.backend.clone(),
             execution_limit: self.execution_limit,
             execution_start: self.execution_start,
-        };
+            max_threads: self.max_threads,
+            package: self.package.clone(),
+            embedded_files: self.embedded_files.clone(),
+            test_filter: self.test_filter.clone(),
+            test_results: Vec::new(),
+            coverage: self.coverage.clone(),
+            profile: None,
+            benches: None,
+            optimize: self.optimize,
+            fold: self.fold,
+            memo: self.memo.clone(),
+            memo_limit: self.memo_limit,
+            memo_scope: self.memo_scope,
+        }
+    }
+    pub(crate) fn spawn(
+        &mut self,
+        capture_count: usize,
+        f: impl FnOnce(&mut Self) -> UiuaResult + Send + 'static,
+    ) -> UiuaResult<Value> {
+        if self.stack.len() < capture_count {
+            return Err(self.error(format!(
+                "Excepted at least {} value(s) on the stack, but there are {}",
+                capture_count,
+                self.stack.len()
+            )))?;
+        }
+        let mut env = self.thread_env();
+        env.stack = self
+            .stack
+            .drain(self.stack.len() - capture_count..)
+            .collect();
         self.backend
             .spawn(env, Box::new(f))
             .map(Value::from)
@@ -15,17 +15,20 @@ use std::{
 };
 
 use bufreaderwriter::seq::BufReaderWriterSeq;
+use crossbeam_channel::{Receiver, Sender};
 use dashmap::DashMap;
 use enum_iterator::Sequence;
 use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
 use image::{DynamicImage, ImageOutputFormat};
+use memmap2::Mmap;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use regex::Regex;
 use tinyvec::tiny_vec;
 
 use crate::{
-    array::Array, cowslice::CowSlice, function::Function, grid_fmt::GridFmt, primitive::PrimDoc,
-    value::Value, Uiua, UiuaError, UiuaResult,
+    array::Array, cowslice::CowSlice, ffi::FfiType, function::Function, grid_fmt::GridFmt,
+    lex::Span, primitive::PrimDoc, value::Value, Uiua, UiuaError, UiuaResult,
 };
 
 pub fn example_ua<T>(f: impl FnOnce(&mut String) -> T) -> T {
@@ -42,6 +45,24 @@ Square_Double_Increment"
     f(&mut EXAMPLE_UA.lock())
 }
 
+/// Read the source of a file to be imported
+///
+/// Files embedded in the runtime take precedence over the backend's files.
+/// If `example.ua` does not exist, the built-in example file is used instead.
+pub(crate) fn import_source(env: &Uiua, path: &str) -> Result<String, String> {
+    if let Some(source) = env.embedded_files.get(path) {
+        return Ok(source.clone());
+    }
+    let bytes = env.backend.file_read_all(path).or_else(|e| {
+        if path == "example.ua" {
+            Ok(example_ua(|ex| ex.as_bytes().to_vec()))
+        } else {
+            Err(e)
+        }
+    })?;
+    String::from_utf8(bytes).map_err(|e| format!("Failed to read file: {e}"))
+}
+
 macro_rules! sys_op {
     ($(
         $(#[doc = $doc:literal])*
@@ -163,7 +184,7 @@ sys_op! {
     (1, Import, "&i", "import"),
     /// Close a stream by its handle
     ///
-    /// This will close files, tcp listeners, and tcp sockets.
+    /// This will close files, tcp listeners, tcp sockets, udp sockets, and channel ends.
     (1(0), Close, "&cl", "close handle"),
     /// Open a file and return a handle to it
     ///
@@ -189,6 +210,20 @@ sys_op! {
     ///
     /// Expects a path and returns a [rank]`1` numeric array.
     (1, FReadAllBytes, "&frab", "file - read all to bytes"),
+    /// Memory-map a file as a byte array
+    ///
+    /// Expects a path and returns a [rank]`1` numeric array.
+    /// Unlike [&frab], the file is not read up front. Its pages are loaded as they are used, so taking slices of huge files is cheap.
+    /// Modifying the array makes a copy. The file itself is never written to.
+    ///
+    /// See also: [&fmn]
+    (1, FMapBytes, "&fmb", "file - map bytes"),
+    /// Memory-map a file as an array of numbers
+    ///
+    /// Expects a path and returns a [rank]`1` numeric array.
+    /// The file is read as 64-bit floats in the platform's byte order, so its length must be a multiple of 8.
+    /// Like [&fmb], the file is not read up front, and modifying the array makes a copy.
+    (1, FMapNums, "&fmn", "file - map numbers"),
     /// Write the entire contents of an array to a file
     ///
     /// Expects a path and a [rank]`1` array or either numbers or characters.
@@ -313,6 +348,79 @@ sys_op! {
     (2(0), TcpSetWriteTimeout, "&tcpswt", "tcp - set write timeout"),
     /// Get the connection address of a TCP socket
     (1, TcpAddr, "&tcpaddr", "tcp - address"),
+    /// Create a UDP socket and bind it to an address
+    ///
+    /// Use port `0` to let the operating system choose a port.
+    /// The chosen address can be retrieved with [&udpaddr].
+    (1, UdpBind, "&udpb", "udp - bind"),
+    /// Send a datagram from a UDP socket to an address
+    ///
+    /// The first argument is the data, the second is the destination address, and the third is the socket handle.
+    /// The data may be a string or a byte array.
+    (3(0), UdpSendTo, "&udps", "udp - send to"),
+    /// Receive a datagram with a UDP socket
+    ///
+    /// The first argument is the maximum number of bytes to receive, and the second is the socket handle.
+    /// The address of the sender is pushed first, and then the received bytes.
+    /// At most 65507 bytes, the largest possible UDP payload, are received, even if the maximum is higher.
+    /// The rest of a datagram that does not fit is discarded.
+    (2(2), UdpRecvFrom, "&udpr", "udp - receive from"),
+    /// Set whether a UDP socket is in non-blocking mode
+    ///
+    /// The first argument is `1` to make the socket non-blocking or `0` to make it blocking, and the second is the socket handle.
+    (2(0), UdpSetNonBlocking, "&udpsnb", "udp - set non-blocking"),
+    /// Set the read timeout of a UDP socket in seconds
+    (2(0), UdpSetReadTimeout, "&udpsrt", "udp - set read timeout"),
+    /// Set the write timeout of a UDP socket in seconds
+    (2(0), UdpSetWriteTimeout, "&udpswt", "udp - set write timeout"),
+    /// Get the local address of a UDP socket
+    (1, UdpAddr, "&udpaddr", "udp - address"),
+    /// Create a channel for sending values between threads
+    ///
+    /// Expects the capacity of the channel.
+    /// A capacity of `0` means that sending blocks until the value is received.
+    /// A capacity of [infinity] means that sending never blocks.
+    ///
+    /// Two handles are returned.
+    /// The sender handle is on top of the stack, and the receiver handle is below it.
+    /// Both ends can be closed with [&cl].
+    /// When the sender is closed, receivers will still get all values that were already sent.
+    ///
+    /// ex: Tx ← &chn ∞
+    ///   : Rx ←
+    ///   : &chs 1 Tx
+    ///   : &chs 2 Tx
+    ///   : &cl Tx
+    ///   : &chr Rx
+    ///   : &chr Rx
+    ///   : &chr Rx
+    ///
+    /// See also: [&chs] [&chr] [&chtr]
+    (1(2), ChannelNew, "&chn", "channel - new"),
+    /// Send a value through a channel
+    ///
+    /// The first argument is the value, and the second is the sender handle.
+    /// If the channel is full, this blocks until there is space.
+    /// It is an error to send on a channel whose receiver has been closed.
+    ///
+    /// See also: [&chn]
+    (2(0), ChannelSend, "&chs", "channel - send"),
+    /// Receive a value from a channel, waiting until one is available
+    ///
+    /// Expects a receiver handle.
+    /// Pushes the received value, and then a boolean indicating whether a value was received.
+    /// If the sender was closed and there are no more values, the boolean is `0` and the value is `0`.
+    ///
+    /// See also: [&chn] [&chtr]
+    (1(2), ChannelRecv, "&chr", "channel - receive"),
+    /// Receive a value from a channel if one is available
+    ///
+    /// Expects a receiver handle.
+    /// Like [&chr], but never waits.
+    /// If there is no value ready, the boolean is `0` and the value is `0`.
+    ///
+    /// See also: [&chn] [&chr]
+    (1(2), ChannelTryRecv, "&chtr", "channel - try receive"),
     /// Make an HTTP request
     ///
     /// Takes in an 1.x HTTP request and returns an HTTP response.
@@ -334,6 +442,64 @@ sys_op! {
     /// - The HTTP version
     /// - The `Host` header (if not defined)
     (2, HttpsWrite, "&httpsw", "http - Make an HTTP request"),
+    /// Find all matches of a regular expression in a string
+    ///
+    /// The first argument is the pattern, and the second is the string to search.
+    /// Returns a [rank]`2` array of [box]ed strings.
+    /// Each row is a match, and each column is a capture group.
+    /// The first column is always the entire match.
+    /// Capture groups that do not participate in a match are empty strings.
+    ///
+    /// ex: &rem "[a-z]+" "hello, world"
+    /// ex: &rem "([a-z]+)([0-9])" "ab1 cd2 ef"
+    ///
+    /// Compiled patterns are cached, so using the same pattern many times is cheap.
+    ///
+    /// See also: [&reim] [&rer]
+    (2, RegexMatch, "&rem", "regex - match"),
+    /// Check whether strings match a regular expression
+    ///
+    /// The first argument is the pattern, and the second is the string or strings to check.
+    /// A single string gives a single boolean.
+    /// A [rank]`2` character array gives one boolean per row.
+    /// An array of [box]ed strings gives a mask with the same shape.
+    ///
+    /// ex: &reim "^[0-9]+$" "123"
+    /// => 1
+    /// ex: &reim "^[0-9]+$" {"123" "abc" "4"}
+    ///
+    /// See also: [&rem] [&rer]
+    (2, RegexIsMatch, "&reim", "regex - is match"),
+    /// Replace matches of a regular expression in a string
+    ///
+    /// The first argument is the pattern, the second is the replacement, and the third is the string.
+    /// If the replacement is a function, it is called with each matched string and must return a string.
+    /// Otherwise, the replacement must be a string, and every match is replaced with it.
+    ///
+    /// ex: &rer "[0-9]+" "#" "a1b22c333"
+    /// => "a#b#c#"
+    /// ex: &rer "[a-z]+" (⇌) "hello, world"
+    ///
+    /// See also: [&rem] [&reim]
+    (3, RegexReplace, "&rer", "regex - replace"),
+    /// Call a function in a C shared library
+    ///
+    /// The first argument is a list of [box]ed strings describing the function:
+    /// the path to the library, the return type, the name of the function, and then the type of each argument.
+    /// The second argument is a list of the arguments to pass.
+    ///
+    /// Supported types are C numeric types like `int`, `unsigned char`, `long`, `size_t`, or `double`,
+    /// `void` for functions that return nothing, `const char*` or `char*` for strings,
+    /// and pointers to numeric types like `double*` for buffers.
+    /// A buffer argument is passed as a numeric array.
+    ///
+    /// Buffers behind pointers that are not `const` may be modified by the function, so their final values are returned.
+    /// If there is only one output, it is returned directly.
+    /// Otherwise, the outputs are returned as a list of [box]es, starting with the return value if there is one.
+    ///
+    /// ex: &ffi {"libm.so.6" "double" "cos" "double"} {0}
+    /// ex: &ffi {"libm.so.6" "double" "pow" "double" "double"} {2 10}
+    (2, Ffi, "&ffi", "foreign function interface"),
 }
 
 /// A handle to an IO stream
@@ -439,6 +605,10 @@ pub trait SysBackend: Any + Send + Sync + 'static {
         self.close(handle)?;
         Ok(())
     }
+    /// Memory-map a file read-only
+    fn file_map(&self, path: &str) -> Result<Mmap, String> {
+        Err("Memory-mapping files is not supported in this environment".into())
+    }
     fn sleep(&self, seconds: f64) -> Result<(), String> {
         Err("Sleeping is not supported in this environment".into())
     }
@@ -486,6 +656,54 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<(), String> {
         Err("TCP sockets are not supported in this environment".into())
     }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_send_to(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    /// Receive a datagram of at most `max_len` bytes
+    ///
+    /// Returns the received bytes and the address of the sender
+    fn udp_recv_from(&self, handle: Handle, max_len: usize) -> Result<(Vec<u8>, String), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_addr(&self, handle: Handle) -> Result<String, String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    /// Create a channel and return its sender and receiver handles
+    ///
+    /// A `capacity` of `None` means the channel is unbounded
+    fn channel(&self, capacity: Option<usize>) -> Result<(Handle, Handle), String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+        Err("Channels are not supported in this environment".into())
+    }
+    /// Receive a value from a channel
+    ///
+    /// Should return `Ok(None)` if the channel is closed and empty,
+    /// or if `block` is `false` and no value is ready.
+    fn channel_recv(&self, handle: Handle, block: bool) -> Result<Option<Value>, String> {
+        Err("Channels are not supported in this environment".into())
+    }
     fn close(&self, handle: Handle) -> Result<(), String> {
         Ok(())
     }
@@ -517,6 +735,16 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
         Err("Making HTTPS requests is not supported in this environment".into())
     }
+    fn ffi(
+        &self,
+        file: &str,
+        return_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        Err("FFI is not supported in this environment".into())
+    }
 }
 
 #[derive(Default)]
@@ -529,6 +757,9 @@ struct GlobalNativeSys {
     files: DashMap<Handle, Buffered<File>>,
     tcp_listeners: DashMap<Handle, TcpListener>,
     tcp_sockets: DashMap<Handle, Buffered<TcpStream>>,
+    udp_sockets: DashMap<Handle, UdpSocket>,
+    channel_senders: DashMap<Handle, Sender<Value>>,
+    channel_receivers: DashMap<Handle, Receiver<Value>>,
     hostnames: DashMap<Handle, String>,
     threads: DashMap<Handle, JoinHandle<UiuaResult<Vec<Value>>>>,
     #[cfg(feature = "audio")]
@@ -551,6 +782,9 @@ impl Default for GlobalNativeSys {
             files: DashMap::new(),
             tcp_listeners: DashMap::new(),
             tcp_sockets: DashMap::new(),
+            udp_sockets: DashMap::new(),
+            channel_senders: DashMap::new(),
+            channel_receivers: DashMap::new(),
             hostnames: DashMap::new(),
             threads: DashMap::new(),
             #[cfg(feature = "audio")]
@@ -569,6 +803,9 @@ impl GlobalNativeSys {
             if !self.files.contains_key(&handle)
                 && !self.tcp_listeners.contains_key(&handle)
                 && !self.tcp_sockets.contains_key(&handle)
+                && !self.udp_sockets.contains_key(&handle)
+                && !self.channel_senders.contains_key(&handle)
+                && !self.channel_receivers.contains_key(&handle)
             {
                 return handle;
             }
@@ -665,6 +902,13 @@ impl SysBackend for NativeSys {
         NATIVE_SYS.files.insert(handle, Buffered::new_writer(file));
         Ok(handle)
     }
+    fn file_map(&self, path: &str) -> Result<Mmap, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        // SAFETY: The map is read-only. Nothing can stop another process from
+        // changing the file while it is mapped, so this is as safe as any
+        // memory-mapped file can be.
+        unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())
+    }
     fn read(&self, handle: Handle, len: usize) -> Result<Vec<u8>, String> {
         Ok(match NATIVE_SYS.get_stream(handle)? {
             SysStream::File(mut file) => {
@@ -900,9 +1144,115 @@ impl SysBackend for NativeSys {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        let socket = UdpSocket::bind(addr).map_err(|e| e.to_string())?;
+        NATIVE_SYS.udp_sockets.insert(handle, socket);
+        Ok(handle)
+    }
+    fn udp_send_to(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        let sent = socket.send_to(data, addr).map_err(|e| e.to_string())?;
+        if sent < data.len() {
+            return Err(format!(
+                "Only {sent} of {} bytes could be sent in one datagram",
+                data.len()
+            ));
+        }
+        Ok(())
+    }
+    fn udp_recv_from(&self, handle: Handle, max_len: usize) -> Result<(Vec<u8>, String), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        // The maximum size of a UDP payload
+        let mut buf = vec![0; max_len.min(65507)];
+        let (len, addr) = socket.recv_from(&mut buf).map_err(|e| e.to_string())?;
+        buf.truncate(len);
+        Ok((buf, addr.to_string()))
+    }
+    fn udp_addr(&self, handle: Handle) -> Result<String, String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        Ok(socket.local_addr().map_err(|e| e.to_string())?.to_string())
+    }
+    fn udp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        socket
+            .set_nonblocking(non_blocking)
+            .map_err(|e| e.to_string())
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        socket.set_read_timeout(timeout).map_err(|e| e.to_string())
+    }
+    fn udp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        socket.set_write_timeout(timeout).map_err(|e| e.to_string())
+    }
+    fn channel(&self, capacity: Option<usize>) -> Result<(Handle, Handle), String> {
+        let (send, recv) = match capacity {
+            Some(cap) => crossbeam_channel::bounded(cap),
+            None => crossbeam_channel::unbounded(),
+        };
+        let send_handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.channel_senders.insert(send_handle, send);
+        let recv_handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.channel_receivers.insert(recv_handle, recv);
+        Ok((send_handle, recv_handle))
+    }
+    fn channel_send(&self, handle: Handle, value: Value) -> Result<(), String> {
+        // Clone the sender so that the map is not locked while blocking
+        let send = NATIVE_SYS
+            .channel_senders
+            .get(&handle)
+            .ok_or_else(|| "Invalid channel sender handle".to_string())?
+            .clone();
+        send.send(value)
+            .map_err(|_| "Cannot send on a channel whose receiver is closed".to_string())
+    }
+    fn channel_recv(&self, handle: Handle, block: bool) -> Result<Option<Value>, String> {
+        // Clone the receiver so that the map is not locked while blocking
+        let recv = NATIVE_SYS
+            .channel_receivers
+            .get(&handle)
+            .ok_or_else(|| "Invalid channel receiver handle".to_string())?
+            .clone();
+        Ok(if block {
+            recv.recv().ok()
+        } else {
+            recv.try_recv().ok()
+        })
+    }
     fn close(&self, handle: Handle) -> Result<(), String> {
         if NATIVE_SYS.files.remove(&handle).is_some()
             || NATIVE_SYS.tcp_listeners.remove(&handle).is_some()
+            || NATIVE_SYS.udp_sockets.remove(&handle).is_some()
+            || NATIVE_SYS.channel_senders.remove(&handle).is_some()
+            || NATIVE_SYS.channel_receivers.remove(&handle).is_some()
             || (NATIVE_SYS.tcp_sockets.remove(&handle).is_some()
                 && NATIVE_SYS.hostnames.remove(&handle).is_some())
         {
@@ -1008,6 +1358,17 @@ impl SysBackend for NativeSys {
 
         Ok(s)
     }
+    #[cfg(feature = "ffi")]
+    fn ffi(
+        &self,
+        file: &str,
+        return_ty: FfiType,
+        name: &str,
+        arg_tys: &[FfiType],
+        args: &[Value],
+    ) -> Result<Value, String> {
+        crate::ffi::ffi_call(file, return_ty, name, arg_tys, args)
+    }
 }
 
 /// Takes an HTTP request, validates it, and fixes it (if possible) by adding
@@ -1274,6 +1635,7 @@ impl SysOp {
                 let bytes: Vec<u8> = match data {
                     Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
                     Value::Byte(arr) => arr.data.into(),
+                    Value::Bits(bits) => bits.into_bytes().data.into(),
                     Value::Char(arr) => arr.data.iter().collect::<String>().into(),
                     Value::Func(_) => return Err(env.error("Cannot write function array to file")),
                 };
@@ -1325,12 +1687,30 @@ impl SysOp {
                 let bytes = bytes.into_iter().map(Into::into);
                 env.push(Array::<u8>::from_iter(bytes));
             }
+            SysOp::FMapBytes => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let map = env.backend.file_map(&path).map_err(|e| env.error(e))?;
+                let data = CowSlice::<u8>::from_map(map).expect("bytes always fit");
+                env.push(Array::new(tiny_vec![data.len()], data));
+            }
+            SysOp::FMapNums => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let map = env.backend.file_map(&path).map_err(|e| env.error(e))?;
+                let data = CowSlice::<f64>::from_map(map).ok_or_else(|| {
+                    env.error(format!(
+                        "Cannot map {path} as numbers because \
+                        its length is not a multiple of 8 bytes"
+                    ))
+                })?;
+                env.push(Array::new(tiny_vec![data.len()], data));
+            }
             SysOp::FWriteAll => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 let data = env.pop(2)?;
                 let bytes: Vec<u8> = match data {
                     Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
                     Value::Byte(arr) => arr.data.into(),
+                    Value::Bits(bits) => bits.into_bytes().data.into(),
                     Value::Char(arr) => arr.data.iter().collect::<String>().into(),
                     Value::Func(_) => return Err(env.error("Cannot write function array to file")),
                 };
@@ -1364,20 +1744,14 @@ impl SysOp {
             }
             SysOp::Import => {
                 let path = env.pop(1)?.as_string(env, "Import path must be a string")?;
-                let input = String::from_utf8(
-                    env.backend
-                        .file_read_all(&path)
-                        .or_else(|e| {
-                            if path == "example.ua" {
-                                Ok(example_ua(|ex| ex.as_bytes().to_vec()))
-                            } else {
-                                Err(e)
-                            }
-                        })
-                        .map_err(|e| env.error(e))?,
-                )
-                .map_err(|e| env.error(format!("Failed to read file: {e}")))?;
-                env.import(&input, path.as_ref())?;
+                let importing_file = match env.span() {
+                    Span::Code(span) => span.path,
+                    Span::Builtin => None,
+                };
+                let path = env.resolve_import(importing_file.as_deref(), &path);
+                let input =
+                    import_source(env, &path.to_string_lossy()).map_err(|e| env.error(e))?;
+                env.import(&input, &path)?;
             }
             SysOp::Now => env.push(instant::now() / 1000.0),
             SysOp::ImDecode => {
@@ -1599,6 +1973,124 @@ impl SysOp {
                     .tcp_set_write_timeout(handle, timeout)
                     .map_err(|e| env.error(e))?;
             }
+            SysOp::UdpBind => {
+                let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
+                let handle = env.backend.udp_bind(&addr).map_err(|e| env.error(e))?;
+                env.push(handle);
+            }
+            SysOp::UdpSendTo => {
+                let data = env
+                    .pop(1)?
+                    .into_bytes(env, "Data must be a string or byte array")?;
+                let addr = env.pop(2)?.as_string(env, "Address must be a string")?;
+                let handle = env
+                    .pop(3)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_send_to(handle, &data, &addr)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::UdpRecvFrom => {
+                let count = env.pop(1)?.as_nat(env, "Count must be an integer")?;
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let (bytes, addr) = env
+                    .backend
+                    .udp_recv_from(handle, count)
+                    .map_err(|e| env.error(e))?;
+                env.push(addr);
+                env.push(bytes);
+            }
+            SysOp::UdpAddr => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let addr = env.backend.udp_addr(handle).map_err(|e| env.error(e))?;
+                env.push(addr);
+            }
+            SysOp::UdpSetNonBlocking => {
+                let non_blocking = env.pop(1)?.as_bool(env, "Non-blocking must be a boolean")?;
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_set_non_blocking(handle, non_blocking)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::UdpSetReadTimeout => {
+                let timeout = env.pop(1)?.as_num(env, "Timeout must be a number")?.abs();
+                let timeout = if timeout.is_infinite() {
+                    None
+                } else {
+                    Some(Duration::from_secs_f64(timeout))
+                };
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_set_read_timeout(handle, timeout)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::UdpSetWriteTimeout => {
+                let timeout = env.pop(1)?.as_num(env, "Timeout must be a number")?.abs();
+                let timeout = if timeout.is_infinite() {
+                    None
+                } else {
+                    Some(Duration::from_secs_f64(timeout))
+                };
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_set_write_timeout(handle, timeout)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::ChannelNew => {
+                let capacity = env
+                    .pop(1)?
+                    .as_num(env, "Channel capacity must be a natural number or infinity")?;
+                let capacity = if capacity.is_infinite() && capacity > 0.0 {
+                    None
+                } else if capacity.fract() == 0.0 && capacity >= 0.0 {
+                    Some(capacity as usize)
+                } else {
+                    return Err(env.error("Channel capacity must be a natural number or infinity"));
+                };
+                let (send, recv) = env.backend.channel(capacity).map_err(|e| env.error(e))?;
+                env.push(recv);
+                env.push(send);
+            }
+            SysOp::ChannelSend => {
+                let value = env.pop(1)?;
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .channel_send(handle, value)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::ChannelRecv | SysOp::ChannelTryRecv => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let block = *self == SysOp::ChannelRecv;
+                let value = env
+                    .backend
+                    .channel_recv(handle, block)
+                    .map_err(|e| env.error(e))?;
+                let received = value.is_some();
+                env.push(value.unwrap_or_else(|| 0u8.into()));
+                env.push(received);
+            }
             SysOp::HttpsWrite => {
                 let http = env
                     .pop(1)?
@@ -1643,11 +2135,147 @@ impl SysOp {
                     .change_directory(&path)
                     .map_err(|e| env.error(e))?;
             }
+            SysOp::RegexMatch => {
+                let pattern = env.pop(1)?.as_string(env, "Pattern must be a string")?;
+                let target = env.pop(2)?.as_string(env, "Target must be a string")?;
+                let regex = cached_regex(&pattern).map_err(|e| env.error(e))?;
+                let group_count = regex.captures_len();
+                let mut matches = Vec::new();
+                for caps in regex.captures_iter(&target) {
+                    for i in 0..group_count {
+                        let group = caps.get(i).map_or("", |m| m.as_str()).to_string();
+                        matches.push(Arc::new(Function::constant(group)));
+                    }
+                }
+                let shape = tiny_vec![matches.len() / group_count, group_count];
+                env.push(Array::new(shape, matches));
+            }
+            SysOp::RegexIsMatch => {
+                let pattern = env.pop(1)?.as_string(env, "Pattern must be a string")?;
+                let target = env.pop(2)?;
+                let regex = cached_regex(&pattern).map_err(|e| env.error(e))?;
+                match target {
+                    Value::Char(arr) if arr.rank() <= 1 => {
+                        let s: String = arr.data.iter().collect();
+                        env.push(regex.is_match(&s));
+                    }
+                    Value::Char(arr) if arr.rank() == 2 => {
+                        let mask: Vec<bool> = arr
+                            .row_slices()
+                            .map(|row| regex.is_match(&row.iter().collect::<String>()))
+                            .collect();
+                        env.push(Array::<u8>::from(mask));
+                    }
+                    Value::Func(arr) => {
+                        let mut mask = Vec::with_capacity(arr.flat_len());
+                        for f in &arr.data {
+                            let s = match f.as_constant() {
+                                Some(val) => val.as_string(env, "Boxed targets must be strings")?,
+                                None => {
+                                    return Err(env.error(format!(
+                                        "Function array as regex target must be all boxes, \
+                                        but {f} is not a box"
+                                    )))
+                                }
+                            };
+                            mask.push(regex.is_match(&s) as u8);
+                        }
+                        env.push(Array::<u8>::new(arr.shape.clone(), mask));
+                    }
+                    target => {
+                        return Err(env.error(format!(
+                            "Regex target must be a string, a rank 2 character array, \
+                            or an array of boxed strings, but it is a rank {} {} array",
+                            target.rank(),
+                            target.type_name()
+                        )))
+                    }
+                }
+            }
+            SysOp::RegexReplace => {
+                let pattern = env.pop(1)?.as_string(env, "Pattern must be a string")?;
+                let replacement = env.pop(2)?;
+                let target = env.pop(3)?.as_string(env, "Target must be a string")?;
+                let regex = cached_regex(&pattern).map_err(|e| env.error(e))?;
+                let replaced = if let Some(f) = replacement.as_function().cloned() {
+                    let mut replaced = String::with_capacity(target.len());
+                    let mut last_end = 0;
+                    for m in regex.find_iter(&target) {
+                        replaced.push_str(&target[last_end..m.start()]);
+                        env.push(m.as_str().to_string());
+                        env.call(f.clone())?;
+                        let new = env
+                            .pop("replacement function's result")?
+                            .as_string(env, "Replacement function must return a string")?;
+                        replaced.push_str(&new);
+                        last_end = m.end();
+                    }
+                    replaced.push_str(&target[last_end..]);
+                    replaced
+                } else {
+                    let replacement =
+                        replacement.as_string(env, "Replacement must be a string or function")?;
+                    regex
+                        .replace_all(&target, regex::NoExpand(&replacement))
+                        .into_owned()
+                };
+                env.push(replaced);
+            }
+            SysOp::Ffi => {
+                let (file, return_ty, name, arg_tys) = ffi_signature(&env.pop(1)?, env)?;
+                let args: Vec<Value> = (env.pop(2)?.into_rows())
+                    .map(
+                        |row| match row.as_function().and_then(|f| f.as_constant()) {
+                            Some(val) => val.clone(),
+                            None => row,
+                        },
+                    )
+                    .collect();
+                let result = env
+                    .backend
+                    .ffi(&file, return_ty, &name, &arg_tys, &args)
+                    .map_err(|e| env.error(e))?;
+                env.push(result);
+            }
         }
         Ok(())
     }
 }
 
+/// Get a compiled regex for a pattern, compiling it only if it has not been seen before
+fn cached_regex(pattern: &str) -> Result<Regex, String> {
+    static CACHE: Lazy<DashMap<String, Regex>> = Lazy::new(DashMap::new);
+    if let Some(regex) = CACHE.get(pattern) {
+        return Ok(regex.clone());
+    }
+    let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex: {e}"))?;
+    CACHE.insert(pattern.into(), regex.clone());
+    Ok(regex)
+}
+
+/// Parse a foreign function description of the form `{"lib" "return type" "name" "arg type"...}`
+fn ffi_signature(value: &Value, env: &Uiua) -> UiuaResult<(String, FfiType, String, Vec<FfiType>)> {
+    let requirement = "FFI description must be a list of boxed strings";
+    let strings: Vec<String> = match value {
+        Value::Func(arr) if arr.rank() == 1 => (arr.data.iter())
+            .map(|f| match f.as_constant() {
+                Some(Value::Char(s)) if s.rank() <= 1 => Ok(s.data.iter().collect()),
+                _ => Err(env.error(requirement)),
+            })
+            .collect::<UiuaResult<_>>()?,
+        _ => return Err(env.error(requirement)),
+    };
+    let [file, return_ty, name, arg_tys @ ..] = strings.as_slice() else {
+        return Err(env.error(
+            "FFI description must contain a library path, a return type, and a function name",
+        ));
+    };
+    let parse = |ty: &String| ty.parse::<FfiType>().map_err(|e| env.error(e));
+    let return_ty = parse(return_ty)?;
+    let arg_tys = arg_tys.iter().map(parse).collect::<UiuaResult<_>>()?;
+    Ok((file.clone(), return_ty, name.clone(), arg_tys))
+}
+
 fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String>)> {
     let mut strings = Vec::new();
     match value {
@@ -1694,7 +2322,7 @@ fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String
                 )))
             }
         },
-        Value::Num(_) | Value::Byte(_) => {
+        Value::Num(_) | Value::Byte(_) | Value::Bits(_) => {
             return Err(env.error(format!(
                 "Command must be a string or function array, but it is {}s",
                 value.type_name()
@@ -0,0 +1,112 @@
+//! Results of test scopes and reports about them
+//!
+//! In [`RunMode::Test`](crate::run::RunMode::Test), each `~~~` scope is run as a separate test.
+//! A failing test does not stop the ones after it.
+
+use std::{fmt::Write, path::Path, time::Duration};
+
+use crate::{
+    ast::{Item, Word},
+    lex::CodeSpan,
+    UiuaError,
+};
+
+/// The outcome of running a test scope
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    /// The name of the test
+    ///
+    /// This is the comment on the first line of the scope if there is one,
+    /// or the line the scope starts on otherwise.
+    pub name: String,
+    /// The span of the first item in the scope
+    pub span: Option<CodeSpan>,
+    /// How long the test took to run
+    pub duration: Duration,
+    /// The error that made the test fail, if any
+    pub error: Option<UiuaError>,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Get the name and span of a test scope from its items
+pub(crate) fn test_name(items: &[Item]) -> (String, Option<CodeSpan>) {
+    let span = items.iter().find_map(|item| match item {
+        Item::Words(words) => words.first().map(|word| word.span.clone()),
+        Item::Binding(binding) => Some(binding.name.span.clone()),
+        Item::Import(import) => Some(import.path.span.clone()),
+        Item::Scoped { .. } | Item::ExtraNewlines(_) => None,
+    });
+    if let Some(Item::Words(words)) = items.first() {
+        if let [word] = words.as_slice() {
+            if let Word::Comment(comment) = &word.value {
+                if !comment.trim().is_empty() {
+                    return (comment.trim().into(), span);
+                }
+            }
+        }
+    }
+    let name = match &span {
+        Some(span) => format!("line {}", span.start.line),
+        None => "empty test".into(),
+    };
+    (name, span)
+}
+
+/// Generate a JUnit XML report for a file's test results
+pub fn junit_xml(path: &Path, results: &[TestResult]) -> String {
+    let suite = escape(&path.to_string_lossy());
+    let failures = results.iter().filter(|res| !res.passed()).count();
+    let time: f64 = results.iter().map(|res| res.duration.as_secs_f64()).sum();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    _ = writeln!(
+        xml,
+        "<testsuites tests=\"{}\" failures=\"{failures}\" time=\"{time:.6}\">",
+        results.len()
+    );
+    _ = writeln!(
+        xml,
+        "  <testsuite name=\"{suite}\" tests=\"{}\" failures=\"{failures}\" time=\"{time:.6}\">",
+        results.len()
+    );
+    for res in results {
+        _ = write!(
+            xml,
+            "    <testcase name=\"{}\" classname=\"{suite}\" time=\"{:.6}\"",
+            escape(&res.name),
+            res.duration.as_secs_f64()
+        );
+        match &res.error {
+            None => xml.push_str("/>\n"),
+            Some(error) => {
+                _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                    escape(&error.message()),
+                    escape(&error.to_string())
+                );
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -8,20 +8,27 @@ use std::{
 };
 
 use crate::{
-    algorithm::pervade::*,
+    algorithm::{par::par_map, pervade::*},
     array::*,
+    bits::BitArray,
     function::{Function, Signature},
     grid_fmt::GridFmt,
     primitive::Primitive,
     Uiua, UiuaResult,
 };
 
+/// A Uiua value
+///
+/// More representations may be added, so this enum is not exhaustive.
 #[derive(Clone)]
+#[non_exhaustive]
 pub enum Value {
     Num(Array<f64>),
     Byte(Array<u8>),
     Char(Array<char>),
     Func(Array<Arc<Function>>),
+    /// Booleans packed into bits, made by comparisons
+    Bits(BitArray),
 }
 
 impl Default for Value {
@@ -37,6 +44,7 @@ impl fmt::Debug for Value {
             Self::Byte(array) => array.fmt(f),
             Self::Char(array) => array.fmt(f),
             Self::Func(array) => array.fmt(f),
+            Self::Bits(bits) => bits.fmt(f),
         }
     }
 }
@@ -94,6 +102,10 @@ impl Value {
             Self::Byte(array) => Box::new(array.rows().map(Value::from)),
             Self::Char(array) => Box::new(array.rows().map(Value::from)),
             Self::Func(array) => Box::new(array.rows().map(Value::from)),
+            Self::Bits(bits) => {
+                let rows: Vec<Self> = bits.to_bytes().into_rows().map(Value::from).collect();
+                Box::new(rows.into_iter())
+            }
         }
     }
     pub fn into_rows(self) -> Box<dyn Iterator<Item = Self>> {
@@ -102,6 +114,7 @@ impl Value {
             Self::Byte(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Char(array) => Box::new(array.into_rows().map(Value::from)),
             Self::Func(array) => Box::new(array.into_rows().map(Value::from)),
+            Self::Bits(bits) => Box::new(bits.into_bytes().into_rows().map(Value::from)),
         }
     }
     pub fn into_rows_rev(self) -> Box<dyn Iterator<Item = Self>> {
@@ -110,6 +123,7 @@ impl Value {
             Self::Byte(array) => Box::new(array.into_rows_rev().map(Value::from)),
             Self::Char(array) => Box::new(array.into_rows_rev().map(Value::from)),
             Self::Func(array) => Box::new(array.into_rows_rev().map(Value::from)),
+            Self::Bits(bits) => Box::new(bits.into_bytes().into_rows_rev().map(Value::from)),
         }
     }
     pub fn into_flat_values(self) -> Box<dyn Iterator<Item = Self>> {
@@ -118,22 +132,35 @@ impl Value {
             Self::Byte(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Char(array) => Box::new(array.data.into_iter().map(Value::from)),
             Self::Func(array) => Box::new(array.data.into_iter().map(Value::from)),
+            Self::Bits(bits) => Box::new(bits.into_bytes().data.into_iter().map(Value::from)),
         }
     }
     pub fn type_name(&self) -> &'static str {
         match self {
-            Self::Num(_) | Self::Byte(_) => "number",
+            Self::Num(_) | Self::Byte(_) | Self::Bits(_) => "number",
             Self::Char(_) => "character",
             Self::Func(_) => "function",
         }
     }
     pub fn shape(&self) -> &[usize] {
-        self.generic_ref(Array::shape, Array::shape, Array::shape, Array::shape)
+        match self {
+            Self::Num(array) => array.shape(),
+            Self::Byte(array) => array.shape(),
+            Self::Char(array) => array.shape(),
+            Self::Func(array) => match array.as_constant() {
+                Some(value) => value.shape(),
+                None => array.shape(),
+            },
+            Self::Bits(bits) => bits.shape(),
+        }
     }
     pub fn shape_prefixes_match(&self, other: &Self) -> bool {
         self.shape().iter().zip(other.shape()).all(|(a, b)| a == b)
     }
     pub fn row_count(&self) -> usize {
+        if let Self::Bits(bits) = self {
+            return bits.row_count();
+        }
         self.generic_ref(
             Array::row_count,
             Array::row_count,
@@ -150,6 +177,9 @@ impl Value {
         )
     }
     pub fn flat_len(&self) -> usize {
+        if let Self::Bits(bits) = self {
+            return bits.flat_len();
+        }
         self.generic_ref(
             Array::flat_len,
             Array::flat_len,
@@ -163,25 +193,23 @@ impl Value {
             Self::Byte(array) => array.first_dim_zero().into(),
             Self::Char(array) => array.first_dim_zero().into(),
             Self::Func(array) => array.first_dim_zero().into(),
+            Self::Bits(bits) => bits.to_bytes().first_dim_zero().into(),
         }
     }
     pub fn format_shape(&self) -> FormatShape {
-        self.generic_ref(
-            Array::format_shape,
-            Array::format_shape,
-            Array::format_shape,
-            Array::format_shape,
-        )
+        FormatShape(self.shape())
     }
     pub fn rank(&self) -> usize {
         self.shape().len()
     }
     pub fn shape_mut(&mut self) -> &mut Shape {
+        self.unpack_mut();
         match self {
             Self::Num(array) => &mut array.shape,
             Self::Byte(array) => &mut array.shape,
             Self::Char(array) => &mut array.shape,
             Self::Func(array) => &mut array.shape,
+            Self::Bits(_) => unreachable!(),
         }
     }
     pub(crate) fn validate_shape(&self) {
@@ -215,14 +243,19 @@ impl Value {
                 Ok(value) => value.generic_into(n, b, c, f),
                 Err(array) => f(array),
             },
+            Self::Bits(bits) => b(bits.into_bytes()),
         }
     }
-    pub fn generic_ref<'a, T: 'a>(
-        &'a self,
-        n: impl FnOnce(&'a Array<f64>) -> T,
-        b: impl FnOnce(&'a Array<u8>) -> T,
-        c: impl FnOnce(&'a Array<char>) -> T,
-        f: impl FnOnce(&'a Array<Arc<Function>>) -> T,
+    /// Call a function on the array inside the value
+    ///
+    /// Bit-packed booleans are unpacked into a temporary byte array,
+    /// so the result cannot borrow from the array.
+    pub fn generic_ref<T>(
+        &self,
+        n: impl FnOnce(&Array<f64>) -> T,
+        b: impl FnOnce(&Array<u8>) -> T,
+        c: impl FnOnce(&Array<char>) -> T,
+        f: impl FnOnce(&Array<Arc<Function>>) -> T,
     ) -> T {
         match self {
             Self::Num(array) => n(array),
@@ -235,14 +268,15 @@ impl Value {
                     f(array)
                 }
             }
+            Self::Bits(bits) => b(&bits.to_bytes()),
         }
     }
-    pub fn generic_ref_env<'a, T: 'a>(
-        &'a self,
-        n: impl FnOnce(&'a Array<f64>, &Uiua) -> UiuaResult<T>,
-        b: impl FnOnce(&'a Array<u8>, &Uiua) -> UiuaResult<T>,
-        c: impl FnOnce(&'a Array<char>, &Uiua) -> UiuaResult<T>,
-        f: impl FnOnce(&'a Array<Arc<Function>>, &Uiua) -> UiuaResult<T>,
+    pub fn generic_ref_env<T>(
+        &self,
+        n: impl FnOnce(&Array<f64>, &Uiua) -> UiuaResult<T>,
+        b: impl FnOnce(&Array<u8>, &Uiua) -> UiuaResult<T>,
+        c: impl FnOnce(&Array<char>, &Uiua) -> UiuaResult<T>,
+        f: impl FnOnce(&Array<Arc<Function>>, &Uiua) -> UiuaResult<T>,
         env: &Uiua,
     ) -> UiuaResult<T> {
         self.generic_ref(|a| n(a, env), |a| b(a, env), |a| c(a, env), |a| f(a, env))
@@ -254,10 +288,12 @@ impl Value {
         c: impl FnOnce(&mut Array<char>) -> T,
         f: impl FnOnce(&mut Array<Arc<Function>>) -> T,
     ) -> T {
+        self.unpack_mut();
         match self {
             Self::Num(array) => n(array),
             Self::Byte(array) => b(array),
             Self::Char(array) => c(array),
+            Self::Bits(_) => unreachable!(),
             Self::Func(array) => {
                 if let Some(value) = array.as_constant_mut() {
                     value.generic_mut(n, b, c, f)
@@ -274,6 +310,22 @@ impl Value {
             Self::Byte(array) => array.grid_string(),
             Self::Char(array) => array.grid_string(),
             Self::Func(array) => array.grid_string(),
+            Self::Bits(bits) => bits.to_bytes().grid_string(),
+        }
+    }
+    /// Convert bit-packed booleans into a byte array
+    ///
+    /// Other values are returned unchanged.
+    pub fn unpack(self) -> Self {
+        match self {
+            Self::Bits(bits) => Self::Byte(bits.into_bytes()),
+            value => value,
+        }
+    }
+    /// Convert bit-packed booleans into a byte array in place
+    pub(crate) fn unpack_mut(&mut self) {
+        if let Self::Bits(_) = self {
+            *self = take(self).unpack();
         }
     }
     pub fn as_primitive(&self) -> Option<(Primitive, usize)> {
@@ -452,6 +504,22 @@ impl Value {
                 }
                 result
             }
+            Value::Bits(bits) => {
+                if bits.rank() > 1 {
+                    return Err(
+                        env.error(format!("{requirement}, but its rank is {}", bits.rank()))
+                    );
+                }
+                let mut result = Vec::with_capacity(bits.row_count());
+                for i in 0..bits.flat_len() {
+                    let num = bits.get(i) as u8 as f64;
+                    if !test(num) {
+                        return Err(env.error(requirement));
+                    }
+                    result.push(convert(num));
+                }
+                result
+            }
             value => {
                 return Err(env.error(format!("{requirement}, but it is {}s", value.type_name())))
             }
@@ -570,6 +638,7 @@ impl Value {
             Value::Byte(arr) => arr.convert_with(|n| Arc::new(Function::constant(n))),
             Value::Char(arr) => arr.convert_with(|n| Arc::new(Function::constant(n))),
             Value::Func(arr) => arr,
+            Value::Bits(bits) => Value::Byte(bits.into_bytes()).coerce_to_function(),
         }
     }
     pub fn coerce_as_function(&self) -> Cow<Array<Arc<Function>>> {
@@ -584,6 +653,9 @@ impl Value {
                 Cow::Owned(arr.convert_ref_with(|n| Arc::new(Function::constant(n))))
             }
             Value::Func(arr) => Cow::Borrowed(arr),
+            Value::Bits(bits) => {
+                Cow::Owned((bits.to_bytes()).convert_ref_with(|n| Arc::new(Function::constant(n))))
+            }
         }
     }
 }
@@ -671,7 +743,7 @@ macro_rules! value_un_impl {
             pub fn $name(self, env: &Uiua) -> UiuaResult<Self> {
                 Ok(match self {
                     $(Self::$variant(array) => {
-                        (array.shape, array.data.into_iter().map($name::$f).collect::<Vec<_>>()).into()
+                        (array.shape, par_map(env, &array.data, $name::$f)).into()
                     },)*
                     Value::Func(mut array) => {
                         let mut new_data = Vec::with_capacity(array.flat_len());
@@ -684,6 +756,7 @@ macro_rules! value_un_impl {
                         array.data = new_data.into();
                         array.into()
                     }
+                    Value::Bits(bits) => Value::Byte(bits.into_bytes()).$name(env)?,
                     val => return Err($name::error(val.type_name(), env))
                 })
             }
@@ -726,6 +799,8 @@ macro_rules! value_bin_impl {
                             Err(e) => return Err(e),
                         }
                     },)*
+                    (Value::Bits(a), b) => Value::$name(&Value::Byte(a.to_bytes()), b, env)?,
+                    (a, Value::Bits(b)) => Value::$name(a, &Value::Byte(b.to_bytes()), env)?,
                     (Value::Func(a), b) => {
                         match a.as_constant() {
                             Some(a) => Value::$name(a, b, env)?,
@@ -861,6 +936,69 @@ macro_rules! cmp_impls {
 
 cmp_impls!(is_eq, is_ne, is_lt, is_le, is_gt, is_ge);
 
+macro_rules! packed_cmp_impls {
+    ($($name:ident => $packed:ident),* $(,)?) => {
+        impl Value {
+            $(
+                /// Compare two values, packing a boolean array result into bits
+                pub fn $packed(&self, other: &Self, env: &Uiua) -> UiuaResult<Self> {
+                    let bits = match (self, other) {
+                        (Value::Num(a), Value::Num(b)) => packed_cmp(a, b, $name::num_num),
+                        (Value::Num(a), Value::Byte(b)) => packed_cmp(a, b, $name::num_byte),
+                        (Value::Byte(a), Value::Num(b)) => packed_cmp(a, b, $name::byte_num),
+                        (Value::Byte(a), Value::Byte(b)) => packed_cmp(a, b, $name::generic),
+                        _ => None,
+                    };
+                    if let Some(bits) = bits {
+                        return Ok(Value::Bits(bits));
+                    }
+                    Ok(match self.$name(other, env)? {
+                        Value::Byte(bytes) if bytes.rank() > 0 => match BitArray::from_bytes(&bytes) {
+                            Some(bits) => Value::Bits(bits),
+                            None => Value::Byte(bytes),
+                        },
+                        value => value,
+                    })
+                }
+            )*
+        }
+    };
+}
+
+packed_cmp_impls!(
+    is_eq => is_eq_packed,
+    is_ne => is_ne_packed,
+    is_lt => is_lt_packed,
+    is_le => is_le_packed,
+    is_gt => is_gt_packed,
+    is_ge => is_ge_packed,
+);
+
+/// Compare numbers directly into bits
+///
+/// Only arrays of the same shape and scalars with arrays are handled.
+fn packed_cmp<A, B>(a: &Array<A>, b: &Array<B>, cmp: impl Fn(A, B) -> u8) -> Option<BitArray>
+where
+    A: ArrayValue + Copy,
+    B: ArrayValue + Copy,
+{
+    Some(match (a.rank(), b.rank()) {
+        (0, 0) => return None,
+        (0, _) => {
+            let a = a.data[0];
+            BitArray::from_slice(b.shape.clone(), &b.data, |b| cmp(a, b) == 1)
+        }
+        (_, 0) => {
+            let b = b.data[0];
+            BitArray::from_slice(a.shape.clone(), &a.data, |a| cmp(a, b) == 1)
+        }
+        _ if a.shape == b.shape => {
+            BitArray::from_slices(a.shape.clone(), &a.data, &b.data, |a, b| cmp(a, b) == 1)
+        }
+        _ => return None,
+    })
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -870,6 +1008,8 @@ impl PartialEq for Value {
             (Value::Func(a), Value::Func(b)) => a == b,
             (Value::Num(a), Value::Byte(b)) => a == b,
             (Value::Byte(a), Value::Num(b)) => a == b,
+            (Value::Bits(a), b) => &Value::Byte(a.to_bytes()) == b,
+            (a, Value::Bits(b)) => a == &Value::Byte(b.to_bytes()),
             _ => false,
         }
     }
@@ -886,6 +1026,8 @@ impl PartialOrd for Value {
 impl Ord for Value {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
+            (Value::Bits(a), b) => Value::Byte(a.to_bytes()).cmp(b),
+            (a, Value::Bits(b)) => a.cmp(&Value::Byte(b.to_bytes())),
             (Value::Num(a), Value::Num(b)) => a.cmp(b),
             (Value::Byte(a), Value::Byte(b)) => a.cmp(b),
             (Value::Char(a), Value::Char(b)) => a.cmp(b),
@@ -913,6 +1055,10 @@ impl Hash for Value {
                 1u8.hash(state);
                 arr.hash(state);
             }
+            Value::Bits(bits) => {
+                1u8.hash(state);
+                bits.to_bytes().hash(state);
+            }
             Value::Char(arr) => {
                 2u8.hash(state);
                 arr.hash(state);
@@ -930,6 +1076,7 @@ impl fmt::Display for Value {
         match self {
             Value::Num(n) => n.fmt(f),
             Value::Byte(b) => b.fmt(f),
+            Value::Bits(bits) => bits.to_bytes().fmt(f),
             Value::Char(c) => c.fmt(f),
             Value::Func(func) => {
                 if let Some(val) = func.as_constant() {